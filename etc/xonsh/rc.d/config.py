@@ -26,7 +26,6 @@ del XSH
 def setup():
     from pathlib import Path
     from typing import cast
-    import datetime as dt
     import json
     import os
     import subprocess
@@ -90,7 +89,6 @@ def setup():
     if env.get("XONSH_INTERACTIVE"):
         import tempfile
         import subprocess
-        import webbrowser
 
         # To list available values:
         #
@@ -101,7 +99,6 @@ def setup():
         COLOR_STYLE = "one-dark"
 
         path_jj = Path("~/.cargo/bin/jj").expanduser()
-        path_jump = Path("~/conf/prj/target/release/jump").expanduser()
 
         def capture_text(command, args=()):
             try:
@@ -149,25 +146,6 @@ def setup():
                 os.rmdir(temp)
             return returncode
 
-        def alias_f(args):
-            args = tuple(args)
-
-            # Jump doesn't yet support date arithmetic, so do it here instead.
-            if args == ("y",):
-                yesterday = dt.datetime.now() - dt.timedelta(days=1)
-                target = Path.home() / "file/log" / yesterday.strftime("%Y/%m/%d")
-                return mc(target)
-
-            match capture_text([path_jump], args):
-                case [stdout, _, 0] if stdout.startswith("http:") or stdout.startswith(
-                    "https:"
-                ):
-                    return 0 if webbrowser.open(stdout) else 1
-                case [stdout, _, 0]:
-                    mc(Path(stdout.rstrip()))
-                case other:
-                    return other
-
         def curr_branch():
             """TODO: Move to Rust"""
             command = [
@@ -197,9 +175,17 @@ def setup():
         aliases["camb"] = "cd ~/git/camelot-b"
         aliases["cg"] = alias_cg
         aliases["ec"] = alias_ec
-        aliases["f"] = alias_f
         aliases["mc"] = alias_mc
 
+        # Jump's wrapper, `f`, with completion: directory targets are visited,
+        # URLs open in the browser, commands run, and snippets are printed.
+        # The script is plain Python, and registers the alias itself.
+        match capture_text(["jump", "--init", "xonsh"]):
+            case [stdout, _, 0]:
+                exec(stdout, {"aliases": aliases})
+            case [_, stderr, _]:
+                print(f"error: jump: {stderr}", file=sys.stderr)
+
         prompt_fields = cast(dict, env["PROMPT_FIELDS"])
         prompt_fields["curr_branch"] = curr_branch
         env_name = prompt_fields["env_name"]
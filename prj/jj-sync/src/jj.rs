@@ -11,6 +11,8 @@ use std::{
     process::Command,
 };
 
+use jj_sync::workspace;
+
 use crate::{error::Error, process};
 
 pub use commit::{Commit, CommitId};
@@ -28,10 +30,7 @@ pub use commit::{Commit, CommitId};
 pub fn workspace_root() -> Result<Option<PathBuf>, Error> {
     let cwd = env::current_dir().map_err(|source| Error::file(Path::new("."), source))?;
     let cwd = canonicalize(&cwd)?;
-    Ok(cwd
-        .ancestors()
-        .find(|dir| dir.join(".jj").is_dir())
-        .map(Path::to_path_buf))
+    Ok(workspace::root(&cwd).map(Path::to_path_buf))
 }
 
 /// The git directory holding `workspace`'s commits, or `None` where the
@@ -105,16 +104,10 @@ pub fn commit(revset: &str) -> Result<Option<Commit>, Error> {
     }))
 }
 
-/// `.jj/repo` is the repository directory, except in a secondary workspace,
-/// where it is a file naming the workspace that owns the repository.
-fn repo_dir(workspace: &Path) -> Result<PathBuf, Error> {
-    let jj = workspace.join(".jj");
-    let repo = jj.join("repo");
-    if repo.is_file() {
-        canonicalize(&jj.join(read_trimmed(&repo)?))
-    } else {
-        Ok(repo)
-    }
+/// See [`workspace::repo_dir`], which this reports failures of against
+/// `.jj/repo`.
+fn repo_dir(root: &Path) -> Result<PathBuf, Error> {
+    workspace::repo_dir(root).map_err(|source| Error::file(&root.join(".jj/repo"), source))
 }
 
 fn read_trimmed(path: &Path) -> Result<String, Error> {
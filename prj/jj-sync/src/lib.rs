@@ -0,0 +1,44 @@
+//! Where a Jujutsu workspace, and the repository behind it, live on disk --
+//! read from the files under `.jj`, without running jj.
+//!
+//! The binary asks these questions of the current directory; other tools, such
+//! as `jump`, ask them of any directory, and share the answers so the two
+//! cannot drift apart.
+
+pub mod workspace {
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+    };
+
+    /// The root of the workspace containing `dir`, or `None` where no ancestor
+    /// of it holds a `.jj` directory -- which is jj's own rule for finding a
+    /// workspace.
+    ///
+    /// `dir` should be canonical, so that the root is too.
+    #[must_use]
+    pub fn root(dir: &Path) -> Option<&Path> {
+        dir.ancestors().find(|dir| dir.join(".jj").is_dir())
+    }
+
+    /// The repository directory of `workspace`.
+    ///
+    /// `.jj/repo` is the repository directory, except in a secondary
+    /// workspace, where it is a file naming the repository directory of the
+    /// default one.  That one is made canonical; `.jj/repo` itself is as
+    /// canonical as `workspace`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `.jj/repo` is a file that cannot be read, or names a directory
+    /// that cannot be resolved.
+    pub fn repo_dir(workspace: &Path) -> io::Result<PathBuf> {
+        let jj = workspace.join(".jj");
+        let repo = jj.join("repo");
+        if repo.is_file() {
+            fs::canonicalize(jj.join(fs::read_to_string(&repo)?.trim()))
+        } else {
+            Ok(repo)
+        }
+    }
+}
@@ -0,0 +1,36 @@
+# Generated by `jump --init bash`.  Defines `f`, which follows a jump target:
+# directories are visited (jump creates any marked `mkdir: true`), URLs open
+# in the browser, commands run, and snippets are printed.  An absolute path,
+# as produced by completing a target name, is visited directly.
+f() {
+    local output
+    local -a found
+    if [[ $# -eq 1 && "$1" == /* && -d "$1" ]]; then
+        found=(path "$1")
+    else
+        output=$(command jump --typed "$@") || return
+        IFS=$'\n' read -r -d '' -a found <<<"$output"
+    fi
+    case ${found[0]} in
+        path) cd "${found[1]}" ;;
+        url) if command -v open >/dev/null; then open "${found[1]}"; else xdg-open "${found[1]}"; fi ;;
+        cmd) "${found[@]:1}" ;;
+        *) printf '%s\n' "${found[@]:1}" ;;
+    esac
+}
+
+# Completes target names.  Completing a name that matches a path exactly
+# replaces it with the path.
+_f() {
+    [[ $COMP_CWORD -eq 1 ]] || return
+    local partial=${COMP_WORDS[COMP_CWORD]}
+    local IFS=$'\n'
+    local lines=($(command jump --complete "$partial" 2>/dev/null))
+    if [[ ${#lines[@]} -eq 1 && "${lines[0]%%$'\t'*}" == "$partial" && "${lines[0]}" == *$'\t'/* ]]; then
+        COMPREPLY=("${lines[0]#*$'\t'}")
+    else
+        COMPREPLY=("${lines[@]%%$'\t'*}")
+    fi
+}
+
+complete -o nospace -F _f f
@@ -0,0 +1,134 @@
+//! Targets that depend on the current directory, rather than on any database.
+//!
+//! jj workspaces are found by `jj-sync`'s own [`workspace`] module, which
+//! follows jj's rule: the nearest ancestor holding a `.jj` directory. Git's
+//! rule is the same, for `.git`, so a repository nested in another's workspace
+//! is the nearer of the two.
+//!
+//! As in `jj-sync`, the directory is made canonical before its ancestors are
+//! searched, so every target is canonical, including the default workspace
+//! found through `.jj/repo`.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use jj_sync::workspace;
+
+/// A built-in target, referred to as `@root`, `@crate`, or `@main`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Builtin {
+    /// The root of the jj or git workspace containing the directory.
+    Root,
+    /// The nearest directory holding a `Cargo.toml`.
+    Crate,
+    /// The default workspace of the jj repository containing the directory.
+    Main,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 3] = [Builtin::Root, Builtin::Crate, Builtin::Main];
+
+    /// Returns the built-in target called `name`, without its leading `@`.
+    pub fn parse(name: &str) -> Option<Builtin> {
+        Builtin::ALL.into_iter().find(|b| b.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Root => "root",
+            Builtin::Crate => "crate",
+            Builtin::Main => "main",
+        }
+    }
+
+    /// Returns the canonical directory this target stands for, as seen from
+    /// `dir`, or [`None`] if there is no such directory.
+    pub fn resolve(self, dir: &Path) -> Option<PathBuf> {
+        let dir = fs::canonicalize(dir).ok()?;
+        let nearest = |holds: fn(&Path) -> bool| dir.ancestors().find(|d| holds(d));
+        match self {
+            // A `.git` file, rather than a directory, marks a worktree.  The
+            // nearer root is the longer path, both being ancestors of `dir`.
+            Builtin::Root => workspace::root(&dir)
+                .into_iter()
+                .chain(nearest(|d| d.join(".git").exists()))
+                .max_by_key(|root| root.as_os_str().len())
+                .map(Path::to_path_buf),
+            Builtin::Crate => nearest(|d| d.join("Cargo.toml").is_file()).map(Path::to_path_buf),
+            Builtin::Main => main_workspace(workspace::root(&dir)?),
+        }
+    }
+}
+
+/// Returns the workspace that holds the repository of the jj workspace at
+/// `root`.
+fn main_workspace(root: &Path) -> Option<PathBuf> {
+    let repo = workspace::repo_dir(root).ok()?;
+    // The repository directory is `<workspace>/.jj/repo`.
+    Some(repo.parent()?.parent()?.to_path_buf())
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "@{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mkdirs(root: &Path, dirs: &[&str]) {
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Builtin::parse("crate"), Some(Builtin::Crate));
+        assert_eq!(Builtin::parse("@crate"), None);
+        assert_eq!(Builtin::Main.to_string(), "@main");
+    }
+
+    #[test]
+    fn roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = &fs::canonicalize(dir.path()).unwrap();
+        mkdirs(
+            root,
+            &["jj/.jj", "jj/src/.git", "jj/doc", "git/.git", "none"],
+        );
+        let resolve = |path: &str| Builtin::Root.resolve(&root.join(path));
+        assert_eq!(resolve("jj/doc"), Some(root.join("jj")));
+        assert_eq!(resolve("jj/src"), Some(root.join("jj/src")));
+        assert_eq!(resolve("git"), Some(root.join("git")));
+        assert_eq!(resolve("none"), None);
+    }
+
+    #[test]
+    fn crates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = &fs::canonicalize(dir.path()).unwrap();
+        mkdirs(root, &["ws/member/src"]);
+        fs::write(root.join("ws/Cargo.toml"), "").unwrap();
+        fs::write(root.join("ws/member/Cargo.toml"), "").unwrap();
+        let resolve = |path: &str| Builtin::Crate.resolve(&root.join(path));
+        assert_eq!(resolve("ws/member/src"), Some(root.join("ws/member")));
+        assert_eq!(resolve("ws"), Some(root.join("ws")));
+        assert_eq!(resolve(""), None);
+    }
+
+    #[test]
+    fn main_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(dir.path()).unwrap();
+        mkdirs(&root, &["main/.jj/repo", "main/src", "second/.jj", "plain"]);
+        fs::write(root.join("second/.jj/repo"), "../../main/.jj/repo\n").unwrap();
+        let resolve = |path: &str| Builtin::Main.resolve(&root.join(path));
+        assert_eq!(resolve("main/src"), Some(root.join("main")));
+        assert_eq!(resolve("second"), Some(root.join("main")));
+        assert_eq!(resolve("plain"), None);
+    }
+}
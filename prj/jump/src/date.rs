@@ -0,0 +1,105 @@
+//! Relative date arguments, which shift the date used for `%` components.
+//!
+//! Recognized forms:
+//! - `-N`, `+N` - N days before or after today
+//! - `today`, `yesterday`, `tomorrow`
+//! - `last-WEEKDAY`, `next-WEEKDAY` - e.g., `last-monday` or `next-fri`
+//! - `YYYY-MM-DD` - an absolute date
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+fn shift(today: NaiveDate, days: i64) -> Option<NaiveDate> {
+    let magnitude = Days::new(days.unsigned_abs());
+    if days < 0 {
+        today.checked_sub_days(magnitude)
+    } else {
+        today.checked_add_days(magnitude)
+    }
+}
+
+/// Returns the number of days from `today` to the nearest other `weekday` in
+/// the specified direction: positive for `next`, negative for `last`.
+fn weekday_offset(today: NaiveDate, weekday: Weekday, next: bool) -> i64 {
+    let from = i64::from(today.weekday().num_days_from_monday());
+    let to = i64::from(weekday.num_days_from_monday());
+    if next {
+        (to - from - 1).rem_euclid(7) + 1
+    } else {
+        -((from - to - 1).rem_euclid(7) + 1)
+    }
+}
+
+/// Parses `arg` as a date relative to `today`, returning [`None`] if `arg` is
+/// not a recognized date form.
+#[must_use]
+pub fn parse(arg: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Some(digits) = arg.strip_prefix(['-', '+'])
+        && !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        let days: i64 = arg.parse().ok()?;
+        return shift(today, days);
+    }
+    match arg {
+        "today" => return Some(today),
+        "yesterday" => return shift(today, -1),
+        "tomorrow" => return shift(today, 1),
+        _ => (),
+    }
+    if let Some((direction, day)) = arg.split_once('-')
+        && matches!(direction, "last" | "next")
+        && let Ok(weekday) = day.parse::<Weekday>()
+    {
+        return shift(today, weekday_offset(today, weekday, direction == "next"));
+    }
+    NaiveDate::parse_from_str(arg, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Thursday.
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 27).unwrap()
+    }
+
+    fn date(arg: &str) -> Option<String> {
+        parse(arg, today()).map(|d| d.to_string())
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(date("-1").as_deref(), Some("2025-03-26"));
+        assert_eq!(date("+5").as_deref(), Some("2025-04-01"));
+        assert_eq!(date("-0").as_deref(), Some("2025-03-27"));
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(date("today").as_deref(), Some("2025-03-27"));
+        assert_eq!(date("yesterday").as_deref(), Some("2025-03-26"));
+        assert_eq!(date("tomorrow").as_deref(), Some("2025-03-28"));
+    }
+
+    #[test]
+    fn weekdays() {
+        assert_eq!(date("last-monday").as_deref(), Some("2025-03-24"));
+        assert_eq!(date("last-thu").as_deref(), Some("2025-03-20"));
+        assert_eq!(date("next-thursday").as_deref(), Some("2025-04-03"));
+        assert_eq!(date("next-fri").as_deref(), Some("2025-03-28"));
+    }
+
+    #[test]
+    fn absolute() {
+        assert_eq!(date("2024-02-29").as_deref(), Some("2024-02-29"));
+        assert_eq!(date("2025-02-29"), None);
+    }
+
+    #[test]
+    fn not_dates() {
+        for arg in ["", "-", "42", "-x", "last-", "last-week", "TIK-42"] {
+            assert_eq!(date(arg), None, "{arg}");
+        }
+    }
+}
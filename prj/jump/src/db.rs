@@ -1,30 +1,311 @@
 use serde::Deserialize;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde_saphyr::Spanned;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::{fmt, fs, io};
 
+use crate::expansion;
+
 // Note: Database stores raw target values as String (not PathBuf) to support
 // URLs and arbitrary strings. Type detection happens at resolve time.
 
-/// A single key or list of keys in YAML format.
-#[derive(Deserialize)]
-#[serde(untagged)]
-enum Keys {
-    One(String),
-    Many(Vec<String>),
+/// A target name, as written in YAML.
+pub(crate) enum Name {
+    String(String),
+    /// A scalar that YAML does not consider a string, such as `42`, `null`,
+    /// or `y` (which YAML 1.1 reads as `true`). Names must be quoted to avoid
+    /// this.
+    Other(String),
 }
 
-#[derive(Debug)]
+/// Accepts any scalar, so that non-string names can be reported at their
+/// location rather than failing the whole file.
+struct NameVisitor;
+
+impl Visitor<'_> for NameVisitor {
+    type Value = Name;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a target name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Name, E> {
+        Ok(Name::String(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<Name, E> {
+        Ok(Name::String(v))
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Name, E> {
+        Ok(Name::Other(v.to_string()))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Name, E> {
+        Ok(Name::Other(v.to_string()))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Name, E> {
+        Ok(Name::Other(v.to_string()))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Name, E> {
+        Ok(Name::Other(v.to_string()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Name, E> {
+        Ok(Name::Other("null".to_owned()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(NameVisitor)
+    }
+}
+
+/// How a target's value is interpreted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// Inferred from the value itself, as for `value: name`.
+    Inferred,
+    /// A path, relative to the directory of the defining file unless it is
+    /// absolute or starts with `~` or `$`.
+    Path,
+    /// A URL of any scheme.
+    Url,
+    /// A program and its arguments. The entry's value is the command line,
+    /// for display.
+    Command(Vec<String>),
+    /// Text to be printed verbatim.
+    Snippet,
+}
+
+/// A target written as a mapping, like `name: { url: ..., keys: [...] }`.
+pub(crate) struct Typed {
+    pub(crate) kind: Kind,
+    pub(crate) value: String,
+    /// Further names for the target, besides the entry's key.
+    pub(crate) keys: Option<Spanned<Vec<Spanned<Name>>>>,
+    /// Whether to create the directory, if it does not exist.
+    pub(crate) mkdir: bool,
+}
+
+/// The fields of a typed target.
+const FIELDS: &[&str] = &["path", "url", "cmd", "snippet", "keys", "mkdir"];
+
+impl Typed {
+    fn visit<'de, A: MapAccess<'de>>(mut map: A) -> std::result::Result<Self, A::Error> {
+        let mut typed: Option<(Kind, String)> = None;
+        let mut keys = None;
+        let mut mkdir = None;
+        while let Some(field) = map.next_key::<String>()? {
+            let (kind, value) = match field.as_str() {
+                "mkdir" if mkdir.is_some() => return Err(de::Error::duplicate_field("mkdir")),
+                "mkdir" => {
+                    mkdir = Some(map.next_value()?);
+                    continue;
+                }
+                "keys" if keys.is_some() => return Err(de::Error::duplicate_field("keys")),
+                "keys" => {
+                    let Some(names) = map.next_value::<Option<Spanned<Keys>>>()? else {
+                        continue;
+                    };
+                    let list = match names.value {
+                        Keys::One(name) => {
+                            vec![Spanned::new(name, names.referenced, names.defined)]
+                        }
+                        Keys::Many(list) => list,
+                        Keys::Typed(_) => {
+                            return Err(de::Error::custom("keys must be a name or list of names"));
+                        }
+                    };
+                    keys = Some(Spanned::new(list, names.referenced, names.defined));
+                    continue;
+                }
+                "path" => (Kind::Path, map.next_value()?),
+                "url" => (Kind::Url, map.next_value()?),
+                "snippet" => (Kind::Snippet, map.next_value()?),
+                "cmd" => {
+                    let argv = map.next_value::<Vec<String>>()?;
+                    (Kind::Command(argv.clone()), argv.join(" "))
+                }
+                _ => return Err(de::Error::unknown_field(&field, FIELDS)),
+            };
+            if typed.is_some() {
+                return Err(de::Error::custom(
+                    "expected only one of path, url, cmd, or snippet",
+                ));
+            }
+            typed = Some((kind, value));
+        }
+        let Some((kind, value)) = typed else {
+            return Err(de::Error::custom(
+                "expected one of path, url, cmd, or snippet",
+            ));
+        };
+        let mkdir = mkdir.unwrap_or_default();
+        if mkdir && kind != Kind::Path {
+            return Err(de::Error::custom("mkdir applies only to path targets"));
+        }
+        Ok(Typed {
+            kind,
+            value,
+            keys,
+            mkdir,
+        })
+    }
+}
+
+/// A single key or list of keys in YAML format, or for a typed target, the
+/// mapping that defines it.
+pub(crate) enum Keys {
+    One(Name),
+    Many(Vec<Spanned<Name>>),
+    Typed(Typed),
+}
+
+impl<'de> Deserialize<'de> for Keys {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct KeysVisitor;
+
+        impl<'de> Visitor<'de> for KeysVisitor {
+            type Value = Keys;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a target name, list of names, or typed target")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> std::result::Result<Keys, A::Error> {
+                let mut names = Vec::new();
+                while let Some(name) = seq.next_element()? {
+                    names.push(name);
+                }
+                Ok(Keys::Many(names))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<Keys, A::Error> {
+                Typed::visit(map).map(Keys::Typed)
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_str(v).map(Keys::One)
+            }
+
+            fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_string(v).map(Keys::One)
+            }
+
+            fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_bool(v).map(Keys::One)
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_i64(v).map(Keys::One)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_u64(v).map(Keys::One)
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_f64(v).map(Keys::One)
+            }
+
+            fn visit_unit<E: de::Error>(self) -> std::result::Result<Keys, E> {
+                NameVisitor.visit_unit().map(Keys::One)
+            }
+        }
+
+        deserializer.deserialize_any(KeysVisitor)
+    }
+}
+
+/// The entries of a `jump.yaml` file, in the order they appear. For a typed
+/// target, the key is the target's name, rather than its value.
+pub(crate) struct Document(pub(crate) Vec<(Spanned<String>, Spanned<Keys>)>);
+
+impl Document {
+    /// Parses the `contents` of the specified file.
+    pub(crate) fn parse(path: &Path, contents: &str) -> Result<Self> {
+        let options = serde_saphyr::Options {
+            with_snippet: false,
+            ..serde_saphyr::Options::default()
+        };
+        serde_saphyr::from_str_with_options(contents, options)
+            .map_err(|e| Error::yaml(path.into(), Box::new(e)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct DocumentVisitor;
+
+        impl<'de> Visitor<'de> for DocumentVisitor {
+            type Value = Document;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map from target values to names, or names to typed targets")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> std::result::Result<Document, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Document(entries))
+            }
+
+            fn visit_unit<E: de::Error>(self) -> std::result::Result<Document, E> {
+                Ok(Document(Vec::new()))
+            }
+        }
+
+        deserializer.deserialize_map(DocumentVisitor)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Location {
     pub file: PathBuf,
     pub line: Option<usize>,
+    /// The column within [`Self::line`], if known.
+    pub column: Option<usize>,
 }
 
+impl Location {
+    /// Returns the location of `span` within `file`.
+    fn within(file: &Path, span: serde_saphyr::Location) -> Self {
+        Self {
+            file: file.to_path_buf(),
+            line: usize::try_from(span.line()).ok().filter(|&n| n > 0),
+            column: usize::try_from(span.column()).ok().filter(|&n| n > 0),
+        }
+    }
+}
+
+/// Formats as `file:line:column`, omitting unknown parts.
 impl fmt::Display for Location {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.file.display().fmt(f)?;
         if let Some(line) = self.line {
             write!(f, ":{line}")?;
+            if let Some(column) = self.column {
+                write!(f, ":{column}")?;
+            }
         }
         Ok(())
     }
@@ -45,6 +326,7 @@ impl IntoLocation for PathBuf {
         Location {
             file: self,
             line: None,
+            column: None,
         }
     }
 }
@@ -59,7 +341,18 @@ impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Io(e) => e.fmt(f),
-            Self::Yaml(e) => e.fmt(f),
+            Self::Yaml(e) => {
+                // The location is already part of ours, so don't repeat it.
+                let message = e.to_string();
+                let suffix = e.location().map(|location| {
+                    let (line, column) = (location.line(), location.column());
+                    format!(" at line {line}, column {column}")
+                });
+                match suffix.and_then(|suffix| message.strip_suffix(&suffix)) {
+                    Some(message) => message.fmt(f),
+                    None => message.fmt(f),
+                }
+            }
         }
     }
 }
@@ -81,9 +374,15 @@ impl Error {
         Self::new(file, ErrorKind::Io(cause))
     }
 
+    /// Returns a YAML error located at the line and column reported by
+    /// `cause`, if any.
     #[must_use]
     pub fn yaml(file: PathBuf, cause: Box<serde_saphyr::Error>) -> Self {
-        Self::new(file, ErrorKind::Yaml(cause))
+        let location = match cause.location() {
+            Some(span) => Location::within(&file, span),
+            None => file.into_location(),
+        };
+        Self::new(location, ErrorKind::Yaml(cause))
     }
 }
 
@@ -95,42 +394,202 @@ impl fmt::Display for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-pub struct Database(
-    /// Maps target names to raw target values (paths, URLs, or arbitrary strings).
-    HashMap<String, String>,
-);
+/// A raw target value, and where it was defined.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub value: String,
+    pub kind: Kind,
+    /// Whether to create the target directory, if it does not exist.
+    pub mkdir: bool,
+    pub location: Location,
+}
+
+/// A name defined more than once. The later definition takes effect.
+#[derive(Debug)]
+pub struct Conflict {
+    pub name: String,
+    /// The overridden definition.
+    pub earlier: Entry,
+    /// The definition in effect.
+    pub later: Entry,
+}
+
+impl Conflict {
+    /// Returns true if the definitions are in different files, meaning that a
+    /// later file shadows an earlier one, rather than one file defining the
+    /// name twice.
+    #[must_use]
+    pub fn is_shadowed(&self) -> bool {
+        self.earlier.location.file != self.later.location.file
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (name, earlier, later) = (&self.name, &self.earlier, &self.later);
+        if self.is_shadowed() {
+            write!(
+                f,
+                "{}: {name}: shadows {} from {}",
+                later.location, earlier.value, earlier.location
+            )
+        } else {
+            write!(
+                f,
+                "{}: {name}: already claimed by {} at {}",
+                later.location, earlier.value, earlier.location
+            )
+        }
+    }
+}
+
+/// Something wrong with an entry that doesn't stop the file from loading.
+#[derive(Debug)]
+pub enum ProblemKind {
+    /// A target value was empty.
+    EmptyValue,
+    /// A name was not a string; e.g., `42` or `null`. The entry is skipped.
+    NotAString(String),
+    /// A `%` component of a path is not a valid `strftime` format string,
+    /// which would otherwise fail only when expanded. The entry is skipped.
+    Strftime(String),
+    /// A project's `.jump.yaml` defined a command or snippet, which a cloned
+    /// repository could use to run anything. The entry is skipped.
+    Untrusted(&'static str),
+}
+
+#[derive(Debug)]
+pub struct Problem {
+    pub location: Location,
+    pub kind: ProblemKind,
+}
 
-impl Default for Database {
-    fn default() -> Self {
-        Self::new()
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.location)?;
+        match &self.kind {
+            ProblemKind::EmptyValue => write!(f, "empty target value"),
+            ProblemKind::NotAString(name) => write!(f, "name {name} is not a string; quote it"),
+            ProblemKind::Strftime(component) => {
+                write!(f, "{component} is not a valid strftime format")
+            }
+            ProblemKind::Untrusted(kind) => {
+                write!(f, "{kind} targets are not read from a project file")
+            }
+        }
     }
 }
 
+#[derive(Default)]
+pub struct Database {
+    /// Maps target names to raw target values (paths, URLs, or arbitrary strings).
+    entries: HashMap<String, Entry>,
+    /// Names defined more than once, in the order they were redefined.
+    conflicts: Vec<Conflict>,
+    /// Names a project's own file took over from earlier files, in the order
+    /// they were redefined.
+    shadows: Vec<Conflict>,
+    /// Problems found while reading files, in the order they were found.
+    problems: Vec<Problem>,
+}
+
 impl Database {
     #[must_use]
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self::default()
     }
 
+    /// Adds the entries from the specified file, which override any earlier
+    /// entries of the same names. Each override is recorded as a [`Conflict`],
+    /// and each questionable entry as a [`Problem`].
+    ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be read, or if its syntax is
     /// invalid.
     pub fn read_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
-        let path = path.as_ref();
-        let contents = fs::read_to_string(path).map_err(|e| Error::io(path.into(), e))?;
+        self.read(path.as_ref(), false)
+    }
+
+    /// Like [`Self::read_file`], but for a project's own `.jump.yaml`, whose
+    /// values are paths relative to its directory, unless they are absolute,
+    /// start with `~` or `$`, or are URLs (containing `://`). Typed paths and
+    /// URLs are read as usual; commands and snippets are skipped, each as a
+    /// [`Problem`]. Its overrides of names in earlier files are intended, and
+    /// are recorded apart from other conflicts (see [`Self::shadows`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Self::read_file`].
+    pub fn read_local(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.read(path.as_ref(), true)
+    }
 
-        let yaml: HashMap<String, Keys> =
-            serde_saphyr::from_str(&contents).map_err(|e| Error::yaml(path.into(), Box::new(e)))?;
+    fn read(&mut self, path: &Path, local: bool) -> Result<()> {
+        let contents = fs::read_to_string(path).map_err(|e| Error::io(path.into(), e))?;
 
-        for (value, keys) in yaml {
-            match keys {
-                Keys::One(key) => {
-                    self.0.insert(key, value);
+        let Document(yaml) = Document::parse(path, &contents)?;
+
+        for (key, keys) in yaml {
+            let location = Location::within(path, key.referenced);
+            let inferred = |value: String| Entry {
+                kind: if local && !value.contains("://") {
+                    Kind::Path
+                } else {
+                    Kind::Inferred
+                },
+                value,
+                mkdir: false,
+                location: location.clone(),
+            };
+            let (entry, names) = match keys.value {
+                Keys::One(name) => (inferred(key.value), vec![(name, keys.referenced)]),
+                Keys::Many(names) => {
+                    let names = names.into_iter().map(|n| (n.value, n.referenced));
+                    (inferred(key.value), names.collect())
                 }
-                Keys::Many(keys) => {
-                    for key in keys {
-                        self.0.insert(key, value.clone());
+                Keys::Typed(typed) => {
+                    let entry = Entry {
+                        value: typed.value,
+                        kind: typed.kind,
+                        mkdir: typed.mkdir,
+                        location: location.clone(),
+                    };
+                    let name = (Name::String(key.value), key.referenced);
+                    let keys = typed.keys.map(|keys| keys.value).unwrap_or_default();
+                    let names = keys.into_iter().map(|n| (n.value, n.referenced));
+                    (entry, std::iter::once(name).chain(names).collect())
+                }
+            };
+            if entry.value.is_empty() {
+                self.problem(location.clone(), ProblemKind::EmptyValue);
+            }
+            let dated = match entry.kind {
+                Kind::Inferred => expansion::is_path(&entry.value),
+                Kind::Path => true,
+                Kind::Url | Kind::Command(_) | Kind::Snippet => false,
+            };
+            if dated && let Some(component) = expansion::invalid_strftime(&entry.value) {
+                self.problem(location, ProblemKind::Strftime(component.to_owned()));
+                continue;
+            }
+            if local {
+                let untrusted = match entry.kind {
+                    Kind::Command(_) => Some("cmd"),
+                    Kind::Snippet => Some("snippet"),
+                    Kind::Inferred | Kind::Path | Kind::Url => None,
+                };
+                if let Some(kind) = untrusted {
+                    self.problem(location, ProblemKind::Untrusted(kind));
+                    continue;
+                }
+            }
+            for (name, span) in names {
+                match name {
+                    Name::String(name) => self.insert(name, entry.clone(), local),
+                    Name::Other(name) => {
+                        let location = Location::within(path, span);
+                        self.problem(location, ProblemKind::NotAString(name));
                     }
                 }
             }
@@ -139,23 +598,100 @@ impl Database {
         Ok(())
     }
 
+    fn problem(&mut self, location: Location, kind: ProblemKind) {
+        self.problems.push(Problem { location, kind });
+    }
+
+    fn insert(&mut self, name: String, entry: Entry, local: bool) {
+        if let Some(earlier) = self.entries.insert(name.clone(), entry.clone()) {
+            let conflict = Conflict {
+                name,
+                earlier,
+                later: entry,
+            };
+            if local && conflict.is_shadowed() {
+                self.shadows.push(conflict);
+            } else {
+                self.conflicts.push(conflict);
+            }
+        }
+    }
+
     #[must_use]
     pub fn get(&self, name: &str) -> Option<&String> {
-        self.0.get(name)
+        self.entries.get(name).map(|entry| &entry.value)
+    }
+
+    /// Returns the entry for `name`, including where it was defined.
+    #[must_use]
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    /// Returns the stored copy of `name`, along with its entry.
+    #[must_use]
+    pub fn named(&self, name: &str) -> Option<(&str, &Entry)> {
+        self.entries
+            .get_key_value(name)
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Returns every name defined more than once, in the order they were
+    /// redefined, except those a project's own file takes over from earlier
+    /// files.
+    #[must_use]
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Returns every name a project's own file takes over from earlier files,
+    /// in the order they were redefined. See [`Self::read_local`].
+    #[must_use]
+    pub fn shadows(&self) -> &[Conflict] {
+        &self.shadows
+    }
+
+    /// Returns the problems found in every file read so far.
+    #[must_use]
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// Returns every name, along with its entry, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Entry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Returns every name that starts with `prefix`, along with its entry, in
+    /// no particular order.
+    pub fn prefixed<'a>(&'a self, prefix: &str) -> impl Iterator<Item = (&'a str, &'a Entry)> {
+        self.iter()
+            .filter(move |(name, _)| name.starts_with(prefix))
     }
 }
 
+/// Returns a database read from `yaml`, and the path of the temporary file it
+/// was read from, which no longer exists.
+#[cfg(test)]
+pub(crate) fn from_yaml(yaml: &str) -> (Database, PathBuf) {
+    use std::io::Write;
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(yaml.as_bytes()).unwrap();
+    let mut db = Database::new();
+    db.read_file(file.path()).unwrap();
+    (db, file.path().to_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
 
     fn parse(yaml: &str) -> Database {
-        let mut file = tempfile::NamedTempFile::new().unwrap();
-        file.write_all(yaml.as_bytes()).unwrap();
-        let mut db = Database::new();
-        db.read_file(file.path()).unwrap();
-        db
+        from_yaml(yaml).0
     }
 
     #[test]
@@ -207,4 +743,231 @@ mod tests {
         let db = parse("https://example.com: ex\n");
         assert_eq!(db.get("ex"), Some(&"https://example.com".into()));
     }
+
+    #[test]
+    fn entries_know_their_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"~/conf: [c, conf]\n").unwrap();
+        let mut db = Database::new();
+        db.read_file(file.path()).unwrap();
+        let mut entries = db.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|&(name, _)| name);
+        assert_eq!(entries.len(), 2);
+        for (name, entry) in entries {
+            assert!(name == "c" || name == "conf");
+            assert_eq!(entry.value, "~/conf");
+            assert_eq!(entry.location.file, file.path());
+        }
+    }
+
+    #[test]
+    fn entries_know_their_line() {
+        let db = parse("# comment\n~/conf: c\n\n~/file: [f, file]\n");
+        let line = |name| db.iter().find(|&(n, _)| n == name).unwrap().1.location.line;
+        assert_eq!(line("c"), Some(2));
+        assert_eq!(line("f"), Some(4));
+        assert_eq!(line("file"), Some(4));
+    }
+
+    #[test]
+    fn entries_know_their_column() {
+        let db = parse("  ~/conf: c\n");
+        let entry = db.iter().next().unwrap().1;
+        assert_eq!(entry.location.line, Some(1));
+        assert_eq!(entry.location.column, Some(3));
+    }
+
+    #[test]
+    fn yaml_error_location() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"~/conf: c\n  ~/file: : f\n").unwrap();
+        let err = Database::new().read_file(file.path()).unwrap_err();
+        assert_eq!(err.location.line, Some(2));
+        assert!(err.location.column.is_some());
+        let prefix = format!("{}:2:", file.path().display());
+        assert!(err.to_string().starts_with(&prefix), "{err}");
+    }
+
+    #[test]
+    fn problems() {
+        let db = parse("\"\": empty\n~/x: [ok, 42]\n~/y: null\n");
+        let problems = db
+            .problems()
+            .iter()
+            .map(|p| (p.location.line.unwrap(), &p.kind))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            problems.as_slice(),
+            [
+                (1, ProblemKind::EmptyValue),
+                (2, ProblemKind::NotAString(n42)),
+                (3, ProblemKind::NotAString(null)),
+            ] if n42 == "42" && null == "null"
+        ));
+        assert_eq!(db.get("ok"), Some(&"~/x".into()));
+    }
+
+    #[test]
+    fn invalid_strftime() {
+        let db = parse("~/log/%Y/%Q: [l, bad]\n~/log: log\n");
+        let [problem] = db.problems() else {
+            panic!("expected one problem: {:?}", db.problems());
+        };
+        assert!(matches!(&problem.kind, ProblemKind::Strftime(q) if q == "%Q"));
+        assert_eq!(problem.location.line, Some(1));
+        assert!(
+            problem
+                .to_string()
+                .ends_with(":1:1: %Q is not a valid strftime format")
+        );
+        assert_eq!(db.get("bad"), None);
+        assert_eq!(db.get("log"), Some(&"~/log".into()));
+    }
+
+    #[test]
+    fn typed_targets() {
+        let db = parse(concat!(
+            "notes: { path: ./notes, keys: [nt, notes-dir] }\n",
+            "web: { url: https://example.com }\n",
+            "status:\n  cmd: [git, status]\n  keys: st\n",
+            "sig: { snippet: hello }\n",
+        ));
+        let entry = |name| db.entry(name).unwrap();
+        assert_eq!(entry("notes").kind, Kind::Path);
+        assert_eq!(entry("nt").value, "./notes");
+        assert_eq!(entry("notes-dir").location.line, Some(1));
+        assert_eq!(entry("web").kind, Kind::Url);
+        assert_eq!(
+            entry("st").kind,
+            Kind::Command(vec!["git".into(), "status".into()])
+        );
+        assert_eq!(entry("status").value, "git status");
+        assert_eq!(entry("status").location.line, Some(3));
+        assert_eq!(entry("sig").kind, Kind::Snippet);
+        assert!(!entry("notes").mkdir);
+        let db = parse("log: { path: ~/log/%Y, mkdir: true }\n");
+        assert!(db.entry("log").unwrap().mkdir);
+        assert_eq!(db.get("c"), None);
+    }
+
+    #[test]
+    fn invalid_typed_targets() {
+        for yaml in [
+            "x: { keys: [y] }\n",
+            "x: { path: ~/x, url: https://example.com }\n",
+            "x: { paht: ~/x }\n",
+            "x: { cmd: git }\n",
+            "x: { url: u, keys: { path: p } }\n",
+            "x: { url: u, mkdir: true }\n",
+        ] {
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(yaml.as_bytes()).unwrap();
+            let err = Database::new().read_file(file.path()).unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::Yaml(_)), "{yaml}");
+        }
+    }
+
+    #[test]
+    fn typed_strftime() {
+        let db = parse("log: { path: log/%Q }\nweb: { url: https://x/%Q }\n");
+        let [problem] = db.problems() else {
+            panic!("expected one problem: {:?}", db.problems());
+        };
+        assert!(matches!(&problem.kind, ProblemKind::Strftime(q) if q == "%Q"));
+        assert_eq!(problem.location.line, Some(1));
+        assert_eq!(db.get("log"), None);
+        assert_eq!(db.get("web"), Some(&"https://x/%Q".into()));
+    }
+
+    #[test]
+    fn local_values() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"docs: docs\n~/x: x\nhttps://example.com: web\nsite: { url: u }\n")
+            .unwrap();
+        let mut db = Database::new();
+        db.read_local(file.path()).unwrap();
+        let kind = |name| &db.entry(name).unwrap().kind;
+        assert_eq!(kind("docs"), &Kind::Path);
+        assert_eq!(kind("x"), &Kind::Path);
+        assert_eq!(kind("web"), &Kind::Inferred);
+        assert_eq!(kind("site"), &Kind::Url);
+        assert!(db.problems().is_empty());
+    }
+
+    #[test]
+    fn local_commands_are_skipped() {
+        let (mut db, _) = from_yaml("conf: { cmd: [true] }\n~/docs: docs\n");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"conf: { cmd: [rm, -rf, /] }\nsig: { snippet: s }\ndocs: docs\n")
+            .unwrap();
+        db.read_local(file.path()).unwrap();
+        assert!(matches!(
+            db.problems(),
+            [
+                Problem {
+                    kind: ProblemKind::Untrusted("cmd"),
+                    ..
+                },
+                Problem {
+                    kind: ProblemKind::Untrusted("snippet"),
+                    ..
+                },
+            ]
+        ));
+        assert_eq!(db.get("conf"), Some(&"true".into()));
+        assert_eq!(db.get("sig"), None);
+        assert!(db.conflicts().is_empty());
+        let [shadow] = db.shadows() else {
+            panic!("expected one shadow: {:?}", db.shadows());
+        };
+        assert_eq!(shadow.name, "docs");
+    }
+
+    #[test]
+    fn duplicate_within_file() {
+        let db = parse("~/conf: c\n~/cache: c\n");
+        assert_eq!(db.get("c"), Some(&"~/cache".into()));
+        let [conflict] = db.conflicts() else {
+            panic!("expected one conflict");
+        };
+        assert_eq!(conflict.name, "c");
+        assert_eq!(conflict.earlier.value, "~/conf");
+        assert_eq!(conflict.later.value, "~/cache");
+        assert!(!conflict.is_shadowed());
+    }
+
+    #[test]
+    fn shadowed_across_files() {
+        let mut first = tempfile::NamedTempFile::new().unwrap();
+        first.write_all(b"~/conf: c\n").unwrap();
+        let mut second = tempfile::NamedTempFile::new().unwrap();
+        second.write_all(b"~/cache: [x, c]\n").unwrap();
+        let mut db = Database::new();
+        db.read_file(first.path()).unwrap();
+        db.read_file(second.path()).unwrap();
+        let [conflict] = db.conflicts() else {
+            panic!("expected one conflict");
+        };
+        assert!(conflict.is_shadowed());
+        assert_eq!(conflict.earlier.location.file, first.path());
+        assert_eq!(conflict.later.location.file, second.path());
+    }
+
+    #[test]
+    fn no_conflicts() {
+        assert!(
+            parse("~/conf: [c, conf]\n~/file: f\n")
+                .conflicts()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn prefixed_names() {
+        let db = parse("~/conf: [c, conf]\n~/file: [f, file]\n");
+        let mut names = db.prefixed("c").map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort_unstable();
+        assert_eq!(names, ["c", "conf"]);
+        assert_eq!(db.prefixed("x").count(), 0);
+    }
 }
@@ -0,0 +1,618 @@
+//! In-place edits to `jump.yaml` files.
+//!
+//! Edits splice the original text, using the source spans of the parsed
+//! entries, so comments, ordering, and quoting elsewhere in the file are
+//! preserved. Each edit re-reads and re-parses the file.
+
+use std::fmt::Write;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::db::{Document, Error, Keys, Name, Result};
+
+/// Converts a character offset within `text` to a byte offset. Offsets past
+/// the end of `text` map to its length.
+fn byte_offset(text: &str, chars: u64) -> usize {
+    let Ok(chars) = usize::try_from(chars) else {
+        return text.len();
+    };
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Returns the byte range of the node at `location`.
+fn range(text: &str, location: serde_saphyr::Location) -> Range<usize> {
+    let span = location.span();
+    let start = byte_offset(text, span.offset());
+    let end = byte_offset(text, span.offset() + span.len());
+    start..end
+}
+
+/// Returns the byte range of the scalar starting at `start`. The parser's
+/// spans don't reliably end where the scalar does, so this scans for the
+/// closing quote or, for a plain scalar, the end of the line, a comment, a
+/// mapping key's colon, or (within a flow collection) the next separator.
+fn scalar(text: &str, start: usize, flow: bool) -> Range<usize> {
+    let rest = &text[start..];
+    let len = match rest.chars().next() {
+        Some('\'') => {
+            // A doubled quote (`''`) is an escaped quote.
+            let mut i = 1;
+            loop {
+                match rest[i..].find('\'') {
+                    Some(j) if rest[i + j + 1..].starts_with('\'') => i += j + 2,
+                    Some(j) => break i + j + 1,
+                    None => break rest.len(),
+                }
+            }
+        }
+        Some('"') => {
+            let mut escaped = false;
+            rest.char_indices()
+                .skip(1)
+                .find(|&(_, c)| {
+                    let close = c == '"' && !escaped;
+                    escaped = c == '\\' && !escaped;
+                    close
+                })
+                .map_or(rest.len(), |(i, _)| i + 1)
+        }
+        _ => {
+            let end = rest
+                .char_indices()
+                .find(|&(i, c)| {
+                    c == '\n'
+                        || (flow && ",]}".contains(c))
+                        || (c == ':' && rest[i + 1..].starts_with([' ', '\t', '\n']))
+                        || (c == '#' && rest[..i].ends_with([' ', '\t']))
+                })
+                .map_or(rest.len(), |(i, _)| i);
+            rest[..end].trim_end().len()
+        }
+    };
+    start..start + len
+}
+
+/// Returns the byte range of the whole lines overlapping `range`, including
+/// the final newline, if any.
+fn lines(text: &str, range: Range<usize>) -> Range<usize> {
+    let start = text[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[range.end..]
+        .find('\n')
+        .map_or(text.len(), |i| range.end + i + 1);
+    start..end
+}
+
+/// Returns the byte offset of the end of the entry whose key starts at
+/// `start`: the start of the next line that is no more indented than the key,
+/// not counting blank lines in between, or the end of `text`.
+fn entry_end(text: &str, start: usize) -> usize {
+    let indent = start - text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut end = text[start..]
+        .find('\n')
+        .map_or(text.len(), |i| start + i + 1);
+    let mut next = end;
+    while next < text.len() {
+        let line_end = text[next..].find('\n').map_or(text.len(), |i| next + i + 1);
+        let line = &text[next..line_end];
+        let content = line.trim_start_matches([' ', '\t']);
+        if !content.trim().is_empty() {
+            if line.len() - content.len() <= indent {
+                break;
+            }
+            end = line_end;
+        }
+        next = line_end;
+    }
+    end
+}
+
+/// How the names of an entry are written.
+enum Layout {
+    /// `value: name`
+    Scalar,
+    /// `value: [a, b]`, with the byte offset of the closing bracket.
+    Flow { close: usize },
+    /// `value:`, followed by lines like `  - a`.
+    Block,
+    /// `name: { path: value, keys: ... }`, or the same as a block mapping.
+    /// The first name is the entry's key, and the rest are its `keys`, if
+    /// any, written in the specified layout.
+    Typed { keys: Option<Box<Layout>> },
+}
+
+/// An entry as written in the file.
+struct Source {
+    /// The value, or [`None`] for a typed entry.
+    value: Option<String>,
+    /// Byte range of the value, the names, and anything between; or for a
+    /// typed entry, of its whole lines.
+    range: Range<usize>,
+    /// Each name (or [`None`] for a non-string), with its byte range.
+    names: Vec<(Option<String>, Range<usize>)>,
+    layout: Layout,
+}
+
+impl Source {
+    fn position(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .position(|(n, _)| n.as_deref() == Some(name))
+    }
+}
+
+/// Returns the layout of a sequence of `names` starting at byte offset
+/// `start`.
+fn sequence(text: &str, names: &[(Option<String>, Range<usize>)], start: usize) -> Layout {
+    if !text[start..].starts_with('[') {
+        return Layout::Block;
+    }
+    let after = names.last().map_or(start + 1, |(_, r)| r.end);
+    let close = text[after..].find(']').map_or(text.len(), |i| after + i);
+    Layout::Flow { close }
+}
+
+fn sources(path: &Path, text: &str) -> Result<Vec<Source>> {
+    let Document(entries) = Document::parse(path, text)?;
+    let string = |name| match name {
+        Name::String(name) => Some(name),
+        Name::Other(_) => None,
+    };
+    let sources = entries.into_iter().map(|(value, keys)| {
+        let start = range(text, value.referenced).start;
+        let node = range(text, keys.referenced);
+        let (names, layout) = match keys.value {
+            Keys::One(name) => {
+                let name = (string(name), scalar(text, node.start, false));
+                (vec![name], Layout::Scalar)
+            }
+            Keys::Many(names) => {
+                let flow = text[node.start..].starts_with('[');
+                let names = names
+                    .into_iter()
+                    .map(|n| {
+                        let start = range(text, n.referenced).start;
+                        (string(n.value), scalar(text, start, flow))
+                    })
+                    .collect::<Vec<_>>();
+                let layout = sequence(text, &names, node.start);
+                (names, layout)
+            }
+            Keys::Typed(typed) => {
+                let mut names = vec![(Some(value.value), scalar(text, start, false))];
+                let keys = typed.keys.map(|keys| {
+                    let list = range(text, keys.referenced);
+                    // A single name is its own node, rather than a sequence.
+                    let one =
+                        matches!(keys.value.as_slice(), [n] if range(text, n.referenced) == list);
+                    let flow =
+                        text[node.start..].starts_with('{') || text[list.start..].starts_with('[');
+                    for n in keys.value {
+                        let start = range(text, n.referenced).start;
+                        names.push((string(n.value), scalar(text, start, flow)));
+                    }
+                    Box::new(if one {
+                        Layout::Scalar
+                    } else {
+                        sequence(text, &names[1..], list.start)
+                    })
+                });
+                return Source {
+                    value: None,
+                    range: lines(text, start..start).start..entry_end(text, start),
+                    names,
+                    layout: Layout::Typed { keys },
+                };
+            }
+        };
+        let end = match layout {
+            Layout::Scalar => names[0].1.end,
+            Layout::Flow { close } => close + 1,
+            Layout::Block => names.last().map_or(node.end, |(_, r)| r.end),
+            Layout::Typed { .. } => unreachable!("typed entries are returned above"),
+        };
+        Source {
+            value: Some(value.value),
+            range: start..end.min(text.len()),
+            names,
+            layout,
+        }
+    });
+    Ok(sources.collect())
+}
+
+fn is_special_word(s: &str) -> bool {
+    const WORDS: &[&str] = &[
+        "true", "false", "yes", "no", "y", "n", "on", "off", "null", "~",
+    ];
+    WORDS.iter().any(|word| s.eq_ignore_ascii_case(word))
+}
+
+/// Returns `s` as a YAML scalar, quoting it unless it would be read back as
+/// the same string without quotes, both as a mapping key and within a flow
+/// sequence.
+fn quote(s: &str) -> String {
+    let plain = !s.is_empty()
+        && !is_special_word(s)
+        && !s.starts_with(|c: char| c.is_ascii_digit() || "-?:,[]{}#&*!|>'\"%@`.+".contains(c))
+        && !s.ends_with(':')
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || "~/._-$+=:@%?&!*".contains(c));
+    if plain {
+        return s.to_owned();
+    }
+    let mut quoted = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::io(path.into(), e))
+}
+
+fn write(path: &Path, text: &str) -> Result<()> {
+    fs::write(path, text).map_err(|e| Error::io(path.into(), e))
+}
+
+/// Adds `names` for `value` to the file at `path`. If the file already has
+/// an entry for `value`, the names are appended to it; otherwise, a new entry
+/// is appended to the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, parsed, or written.
+pub fn add(path: &Path, names: &[String], value: &str) -> Result<()> {
+    let mut text = read(path)?;
+    let sources = sources(path, &text)?;
+    let quoted = names.iter().map(|n| quote(n)).collect::<Vec<_>>();
+    let Some(source) = sources
+        .iter()
+        .rev()
+        .find(|s| s.value.as_deref() == Some(value))
+    else {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        let names = match quoted.as_slice() {
+            [name] => name.clone(),
+            names => format!("[{}]", names.join(", ")),
+        };
+        writeln!(text, "{}: {names}", quote(value)).expect("strings are writable");
+        return write(path, &text);
+    };
+    match source.layout {
+        Layout::Scalar => {
+            let (_, range) = &source.names[0];
+            let names = format!("[{}, {}]", &text[range.clone()], quoted.join(", "));
+            text.replace_range(range.clone(), &names);
+        }
+        Layout::Flow { close } => {
+            let at = source.names.last().map_or(close, |(_, r)| r.end);
+            let sep = if source.names.is_empty() { "" } else { ", " };
+            text.insert_str(at, &format!("{sep}{}", quoted.join(", ")));
+        }
+        Layout::Block => {
+            let (_, last) = source.names.last().expect("block sequences are not empty");
+            let line = lines(&text, last.clone());
+            let prefix = text[line.start..last.start].to_owned();
+            let mut insert = String::new();
+            if !text[..line.end].ends_with('\n') {
+                insert.push('\n');
+            }
+            for name in &quoted {
+                writeln!(insert, "{prefix}{name}").expect("strings are writable");
+            }
+            text.insert_str(line.end, &insert);
+        }
+        Layout::Typed { .. } => unreachable!("typed entries have no value to match"),
+    }
+    write(path, &text)
+}
+
+/// Returns the byte range to replace, and its replacement, to remove the name
+/// at `index` of `names`, which are written in `layout`. Removing the only name
+/// of a sequence leaves it empty.
+fn unlist(
+    text: &str,
+    names: &[(Option<String>, Range<usize>)],
+    index: usize,
+    layout: &Layout,
+) -> (Range<usize>, &'static str) {
+    let range = names[index].1.clone();
+    match layout {
+        Layout::Scalar => (range, "[]"),
+        Layout::Flow { .. } => match (names.get(index + 1), index.checked_sub(1)) {
+            (Some((_, next)), _) => (range.start..next.start, ""),
+            (None, Some(previous)) => (names[previous].1.end..range.end, ""),
+            (None, None) => (range, ""),
+        },
+        Layout::Block => (lines(text, range), ""),
+        Layout::Typed { .. } => unreachable!("keys are names, not typed entries"),
+    }
+}
+
+/// Removes every occurrence of `name` from the file at `path`, removing any
+/// entry left without names. Removing the key of a typed entry removes the
+/// entry, along with its `keys`. Returns false if the file did not contain the
+/// name.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, parsed, or written.
+pub fn remove(path: &Path, name: &str) -> Result<bool> {
+    let mut text = read(path)?;
+    let mut removed = false;
+    loop {
+        let sources = sources(path, &text)?;
+        let Some((source, index)) = sources.iter().find_map(|s| Some((s, s.position(name)?)))
+        else {
+            break;
+        };
+        let (doomed, replacement) = match &source.layout {
+            // Removing the key of a typed entry removes the whole entry.
+            Layout::Typed { .. } if index == 0 => (source.range.clone(), ""),
+            Layout::Typed { keys: Some(keys) } => {
+                unlist(&text, &source.names[1..], index - 1, keys)
+            }
+            Layout::Typed { keys: None } => unreachable!("the key is the only name"),
+            _ if source.names.len() == 1 => (lines(&text, source.range.clone()), ""),
+            layout => unlist(&text, &source.names, index, layout),
+        };
+        text.replace_range(doomed, replacement);
+        removed = true;
+    }
+    if removed {
+        write(path, &text)?;
+    }
+    Ok(removed)
+}
+
+/// Renames every occurrence of `old` to `new` in the file at `path`. Returns
+/// false if the file did not contain `old`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, parsed, or written.
+pub fn rename(path: &Path, old: &str, new: &str) -> Result<bool> {
+    let mut text = read(path)?;
+    if old == new {
+        // Every replacement would still be `old`, so the loop would not end.
+        let sources = sources(path, &text)?;
+        return Ok(sources.iter().any(|s| s.position(old).is_some()));
+    }
+    let mut renamed = false;
+    loop {
+        let sources = sources(path, &text)?;
+        let Some(range) = sources
+            .iter()
+            .find_map(|s| Some(s.names[s.position(old)?].1.clone()))
+        else {
+            break;
+        };
+        text.replace_range(range, &quote(new));
+        renamed = true;
+    }
+    if renamed {
+        write(path, &text)?;
+    }
+    Ok(renamed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    /// Applies `edit` to a file containing `yaml`, and returns the result.
+    fn edited(yaml: &str, edit: impl FnOnce(&Path)) -> String {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(yaml.as_bytes()).unwrap();
+        edit(file.path());
+        fs::read_to_string(file.path()).unwrap()
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().copied().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("~/conf"), "~/conf");
+        assert_eq!(
+            quote("https://example.com/a?b=c"),
+            "https://example.com/a?b=c"
+        );
+        assert_eq!(quote("$JUMP_HOME/git"), "$JUMP_HOME/git");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("y"), "\"y\"");
+        assert_eq!(quote("42"), "\"42\"");
+        assert_eq!(quote("%Y"), "\"%Y\"");
+        assert_eq!(quote("a b"), "\"a b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn add_new_entry() {
+        let yaml = edited("# Comment\n~/conf: c # trailing\n", |path| {
+            add(path, &names(&["f"]), "~/file").unwrap();
+        });
+        assert_eq!(yaml, "# Comment\n~/conf: c # trailing\n~/file: f\n");
+    }
+
+    #[test]
+    fn add_new_entry_without_trailing_newline() {
+        let yaml = edited("~/conf: c", |path| {
+            add(path, &names(&["f", "file"]), "~/my file").unwrap();
+        });
+        assert_eq!(yaml, "~/conf: c\n\"~/my file\": [f, file]\n");
+    }
+
+    #[test]
+    fn add_to_scalar() {
+        let yaml = edited("~/conf: 'c' # comment\n~/file: f\n", |path| {
+            add(path, &names(&["conf"]), "~/conf").unwrap();
+        });
+        assert_eq!(yaml, "~/conf: ['c', conf] # comment\n~/file: f\n");
+    }
+
+    #[test]
+    fn add_to_flow() {
+        let yaml = edited("~/conf: [c, \"co\"]\n", |path| {
+            add(path, &names(&["conf", "y"]), "~/conf").unwrap();
+        });
+        assert_eq!(yaml, "~/conf: [c, \"co\", conf, \"y\"]\n");
+    }
+
+    #[test]
+    fn add_to_block() {
+        let yaml = edited("~/conf:\n  - c\n  - co\n~/file: f\n", |path| {
+            add(path, &names(&["conf"]), "~/conf").unwrap();
+        });
+        assert_eq!(yaml, "~/conf:\n  - c\n  - co\n  - conf\n~/file: f\n");
+    }
+
+    #[test]
+    fn remove_scalar_entry() {
+        let yaml = edited("# Keep\n~/conf: c # gone\n~/file: f\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(yaml, "# Keep\n~/file: f\n");
+    }
+
+    #[test]
+    fn remove_from_flow() {
+        let first = edited("~/conf: [c, co, conf]\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(first, "~/conf: [co, conf]\n");
+        let last = edited("~/conf: [c, co, conf]\n", |path| {
+            assert!(remove(path, "conf").unwrap());
+        });
+        assert_eq!(last, "~/conf: [c, co]\n");
+        let only = edited("~/conf: [c]\n~/file: f\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(only, "~/file: f\n");
+    }
+
+    #[test]
+    fn remove_from_block() {
+        let yaml = edited("~/conf:\n  - c\n  - conf\n~/file: f\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(yaml, "~/conf:\n  - conf\n~/file: f\n");
+        let yaml = edited("~/conf:\n  - c\n~/file: f\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(yaml, "~/file: f\n");
+    }
+
+    #[test]
+    fn remove_every_occurrence() {
+        let yaml = edited("~/conf: [c, conf]\n~/cache: c\n", |path| {
+            assert!(remove(path, "c").unwrap());
+        });
+        assert_eq!(yaml, "~/conf: [conf]\n");
+    }
+
+    #[test]
+    fn remove_missing() {
+        let yaml = edited("~/conf: c\n", |path| {
+            assert!(!remove(path, "x").unwrap());
+        });
+        assert_eq!(yaml, "~/conf: c\n");
+    }
+
+    #[test]
+    fn remove_typed_entry() {
+        let yaml = "web: { url: https://example.com }\nst:\n  cmd: [git, status]\n\n  keys: [s]\n# Keep\n~/file: f\n";
+        let yaml = edited(yaml, |path| {
+            assert!(remove(path, "st").unwrap());
+            assert!(remove(path, "web").unwrap());
+        });
+        assert_eq!(yaml, "# Keep\n~/file: f\n");
+    }
+
+    #[test]
+    fn remove_typed_keys() {
+        let flow = edited("web: { url: u, keys: [w, www] }\n", |path| {
+            assert!(remove(path, "w").unwrap());
+        });
+        assert_eq!(flow, "web: { url: u, keys: [www] }\n");
+        let only = edited("web: { url: u, keys: w }\n", |path| {
+            assert!(remove(path, "w").unwrap());
+        });
+        assert_eq!(only, "web: { url: u, keys: [] }\n");
+        let block = edited("web:\n  url: u\n  keys:\n    - w\n", |path| {
+            assert!(remove(path, "w").unwrap());
+        });
+        assert_eq!(block, "web:\n  url: u\n  keys:\n");
+    }
+
+    #[test]
+    fn rename_typed() {
+        let yaml = edited(
+            "web: { url: u, keys: w }\nx:\n  snippet: w\n  keys: [w]\n",
+            |path| {
+                assert!(rename(path, "web", "site").unwrap());
+                assert!(rename(path, "w", "s").unwrap());
+            },
+        );
+        assert_eq!(
+            yaml,
+            "site: { url: u, keys: s }\nx:\n  snippet: w\n  keys: [s]\n"
+        );
+    }
+
+    #[test]
+    fn add_beside_typed() {
+        let yaml = edited("web: { url: ~/x }\n", |path| {
+            add(path, &names(&["x"]), "~/x").unwrap();
+        });
+        assert_eq!(yaml, "web: { url: ~/x }\n~/x: x\n");
+    }
+
+    #[test]
+    fn scalar_ends() {
+        let text = "'it''s' # x";
+        assert_eq!(&text[scalar(text, 0, false)], "'it''s'");
+        let text = r#""a\"b", c"#;
+        assert_eq!(&text[scalar(text, 0, true)], r#""a\"b""#);
+        let text = "a,b # c\n";
+        assert_eq!(&text[scalar(text, 0, false)], "a,b");
+        assert_eq!(&text[scalar(text, 0, true)], "a");
+        let text = "web: { url: u }";
+        assert_eq!(&text[scalar(text, 0, false)], "web");
+    }
+
+    #[test]
+    fn rename_names() {
+        let yaml = edited("~/conf: [c, \"conf\"] # comment\n~/file: conf\n", |path| {
+            assert!(rename(path, "conf", "my conf").unwrap());
+        });
+        assert_eq!(
+            yaml,
+            "~/conf: [c, \"my conf\"] # comment\n~/file: \"my conf\"\n"
+        );
+    }
+
+    #[test]
+    fn rename_to_itself() {
+        let yaml = edited("~/conf: c\n", |path| {
+            assert!(rename(path, "c", "c").unwrap());
+            assert!(!rename(path, "d", "d").unwrap());
+        });
+        assert_eq!(yaml, "~/conf: c\n");
+    }
+}
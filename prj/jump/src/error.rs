@@ -1,23 +1,73 @@
-use std::fmt;
 use std::path::PathBuf;
+use std::{fmt, io};
 
-use crate::{db, expansion};
+use crate::{builtin, db, expansion, history, template};
 
 #[derive(Debug)]
 pub enum Error {
+    /// A prefix matched more than one target name.
+    Ambiguous {
+        name: String,
+        candidates: Vec<(String, db::Location)>,
+    },
+    /// A built-in target does not apply to the current directory.
+    Builtin(builtin::Builtin),
     /// A config file could not be parsed.
     Config(PathBuf, Box<serde_saphyr::Error>),
+    /// Target values refer to each other in a cycle. The first and last
+    /// names are the same.
+    Cycle(Vec<String>),
     /// An error ocurred loading a database.
     Database(db::Error),
+    /// A target name to be added is already defined.
+    Exists {
+        name: String,
+        location: db::Location,
+    },
     /// An error occurred expanding a path.
     Expansion(expansion::Error),
+    /// The history could not be read or updated.
+    History(history::Error),
     /// No target was specified, and no default found.
     Missing,
+    /// A target name to be added or replaced is defined in a project's
+    /// `.jump.yaml`, which is not edited, and would shadow the new definition.
+    Local {
+        name: String,
+        location: db::Location,
+    },
+    /// A target directory could not be created.
+    Mkdir(PathBuf, io::Error),
+    /// No `jump.yaml` was loaded, so there is none to add targets to.
+    NoFile,
+    /// A target path does not exist, and was required to.
+    Nonexistent(PathBuf),
+    /// A target name resolved, but the path below it does not exist.
+    Subpath {
+        name: String,
+        /// The leading component of the name, which resolved as a target.
+        prefix: String,
+        /// The path the prefix resolved to.
+        resolved: PathBuf,
+        /// The deepest existing directory along the path.
+        existing: PathBuf,
+        /// The first component that does not exist.
+        missing: String,
+    },
+    /// A target's value appends a suffix to another target, which is neither
+    /// a path nor a URL.
+    Reference { name: String, referenced: String },
     /// No target was found for the argument.
     Target {
         name: String,
         searched: Vec<PathBuf>,
+        /// Similar target names, and their values.
+        suggestions: Vec<(String, String)>,
     },
+    /// No target path contains the specified path.
+    Unnamed(PathBuf),
+    /// Arguments did not fit a parameterized target.
+    Template(template::Error),
 }
 
 impl std::error::Error for Error {}
@@ -34,20 +84,91 @@ impl From<expansion::Error> for Error {
     }
 }
 
+impl From<history::Error> for Error {
+    fn from(value: history::Error) -> Self {
+        Self::History(value)
+    }
+}
+
+impl From<template::Error> for Error {
+    fn from(value: template::Error) -> Self {
+        Self::Template(value)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            Self::Ambiguous { name, candidates } => {
+                write!(f, "{name}: ambiguous target; candidates:")?;
+                for (candidate, location) in candidates {
+                    write!(f, "\n  {candidate} ({location})")?;
+                }
+                Ok(())
+            }
+            Self::Builtin(builtin) => {
+                let missing = match builtin {
+                    builtin::Builtin::Root => "no jj or git workspace contains",
+                    builtin::Builtin::Crate => "no Cargo.toml is in or above",
+                    builtin::Builtin::Main => "no jj repository contains",
+                };
+                write!(f, "{builtin}: {missing} the current directory")
+            }
             Self::Config(p, e) => write!(f, "{}: {e}", p.display()),
+            Self::Cycle(names) => write!(f, "reference cycle: {}", names.join(" -> ")),
             Self::Database(e) => e.fmt(f),
+            Self::Exists { name, location } => write!(
+                f,
+                "{name}: target already defined at {location}; use --force to replace it"
+            ),
             Self::Expansion(e) => e.fmt(f),
+            Self::History(e) => e.fmt(f),
             Self::Missing => "no default target is configured".fmt(f),
-            Self::Target { name, searched } => {
+            Self::Local { name, location } => write!(
+                f,
+                "{name}: target defined in the project file at {location}, which would \
+                 shadow it; edit that file instead"
+            ),
+            Self::Mkdir(path, e) => write!(f, "{}: cannot create directory: {e}", path.display()),
+            Self::NoFile => "no jump.yaml to add targets to".fmt(f),
+            Self::Nonexistent(path) => write!(f, "{}: no such file or directory", path.display()),
+            Self::Subpath {
+                name,
+                prefix,
+                resolved,
+                existing,
+                missing,
+            } => write!(
+                f,
+                "{name}: no {missing} in {}, below target {prefix} ({})",
+                existing.display(),
+                resolved.display()
+            ),
+            Self::Reference { name, referenced } => write!(
+                f,
+                "{name}: cannot append a path to {referenced}, which is not a path or URL"
+            ),
+            Self::Target {
+                name,
+                searched,
+                suggestions,
+            } => {
                 write!(f, "{name}: target not found; searched:")?;
                 for path in searched {
                     write!(f, "\n  {}", path.display())?;
                 }
+                if !suggestions.is_empty() {
+                    write!(f, "\ndid you mean:")?;
+                    let width = suggestions.iter().map(|(n, _)| n.len()).max();
+                    let width = width.unwrap_or_default();
+                    for (suggestion, value) in suggestions {
+                        write!(f, "\n  {suggestion:width$}  {value}")?;
+                    }
+                }
                 Ok(())
             }
+            Self::Template(e) => e.fmt(f),
+            Self::Unnamed(path) => write!(f, "{}: not below any target", path.display()),
         }
     }
 }
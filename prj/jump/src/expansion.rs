@@ -1,160 +1,435 @@
 //! Target expansion and type detection for jump targets.
 //!
-//! Supports three target types:
+//! The type of a target written as `value: name` is inferred from its value:
 //! - URLs (`http://`, `https://`) - output verbatim
 //! - Paths (`/`, `~`, `$`, `%`) - expanded with variable substitution
 //! - Arbitrary strings - output verbatim
+//!
+//! Typed targets, like `name: { url: ... }`, state their type instead.
+
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::{env, fmt, ptr};
 
-use std::ffi::OsStr;
-use std::os::unix::ffi::OsStrExt;
-use std::path::{Component, Path, PathBuf};
-use std::{env, fmt};
+use chrono::NaiveDate;
+use chrono::format::{Item, StrftimeItems};
 
 #[derive(Debug)]
 pub enum Error {
     /// An expanded target was empty.
     Empty,
-    /// An environment variable was unset.
-    Unset,
+    /// The named environment variable was unset.
+    Unset(String),
+    /// A `%` component was not a valid `strftime` format string.
+    Strftime(String),
+    /// The named user, as in `~user`, does not exist.
+    User(String),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Empty => write!(f, "Empty target"),
-            Self::Unset => write!(f, "Unset variable"),
+            Self::Strftime(format) => write!(f, "Invalid strftime format: {format}"),
+            Self::Unset(name) => write!(f, "Unset variable: {name}"),
+            Self::User(name) => write!(f, "Unknown user: {name}"),
         }
     }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Returns true if `value` is inferred to be a path, rather than output
+/// verbatim.
+pub(crate) fn is_path(value: &str) -> bool {
+    value.starts_with(['/', '~', '$', '%'])
+}
+
+/// Returns true if `value` is inferred to be a URL.
+pub(crate) fn is_url(value: &str) -> bool {
+    value.starts_with("http://") || value.starts_with("https://")
+}
+
+/// Returns the first `%` component of `value` that is not a valid `strftime`
+/// format string.
+pub(crate) fn invalid_strftime(value: &str) -> Option<&str> {
+    value
+        .split('/')
+        .filter(|component| component.starts_with('%'))
+        .find(|component| StrftimeItems::new(component).any(|item| item == Item::Error))
+}
+
 /// Represents the resolved value of a jump target lookup.
 #[derive(Debug)]
 pub enum Target {
     /// A filesystem path (expanded from `~`, `$VAR`, `%date`, or absolute paths).
     Path(PathBuf),
-    /// A URL or arbitrary string (output verbatim).
+    /// A URL (output verbatim).
+    Url(String),
+    /// A program and its arguments.
+    Command(Vec<String>),
+    /// An arbitrary string, or snippet (output verbatim).
     String(String),
 }
 
-enum Expansion<'a, 'b> {
-    Path(&'a Path),
-    PathBuf(PathBuf),
-    Component(Component<'b>),
-    String(String),
+/// Splits `path` on slashes, except within `${...}`, so that a default value
+/// like `${VAR:-~/x}` stays in one piece.
+fn segments(path: &[u8]) -> Vec<&[u8]> {
+    let mut segments = Vec::new();
+    let (mut start, mut depth) = (0, 0_usize);
+    for (i, &b) in path.iter().enumerate() {
+        match b {
+            b'{' if i > 0 && path[i - 1] == b'$' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            b'/' if depth == 0 => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    segments.push(&path[start..]);
+    segments
 }
 
-impl AsRef<Path> for Expansion<'_, '_> {
-    fn as_ref(&self) -> &Path {
-        match self {
-            Self::Path(p) => p,
-            Self::PathBuf(p) => p,
-            Self::Component(c) => c.as_ref(),
-            Self::String(s) => Path::new(s),
+/// Returns the length of the variable name at the start of `s`, which is zero
+/// if `s` does not start with a name.
+fn name_len(s: &[u8]) -> usize {
+    if s.first().is_some_and(u8::is_ascii_digit) {
+        return 0;
+    }
+    s.iter()
+        .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+        .count()
+}
+
+/// Returns the index of the brace closing a `${` whose body starts `s`.
+fn closing_brace(s: &[u8]) -> Option<usize> {
+    let mut depth = 0_usize;
+    for (i, &b) in s.iter().enumerate() {
+        match b {
+            b'{' if i > 0 && s[i - 1] == b'$' => depth += 1,
+            b'}' if depth == 0 => return Some(i),
+            b'}' => depth -= 1,
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Returns the home directory of the named user.
+fn user_home(user: &[u8]) -> Result<PathBuf> {
+    let unknown = || Error::User(String::from_utf8_lossy(user).into_owned());
+    let name = CString::new(user).map_err(|_| unknown())?;
+    let mut buf = vec![0; 1024];
+    loop {
+        // SAFETY: `passwd` is plain old data, for which zeroes are valid.
+        let mut passwd = unsafe { std::mem::zeroed::<libc::passwd>() };
+        let mut result = ptr::null_mut();
+        // SAFETY: Every pointer is valid, and `buf.len()` is the length of
+        // the buffer. On success, `result` points to `passwd`, whose strings
+        // point into `buf`.
+        let status = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                &raw mut passwd,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &raw mut result,
+            )
+        };
+        if status == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
         }
+        if status != 0 || result.is_null() || passwd.pw_dir.is_null() {
+            return Err(unknown());
+        }
+        // SAFETY: `pw_dir` is a nul-terminated string within `buf`.
+        let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+        return Ok(PathBuf::from(OsStr::from_bytes(dir.to_bytes())));
     }
 }
 
 pub struct Expand<'a> {
     home: &'a Path,
+    /// The date used to format `%` components.
+    date: NaiveDate,
+    /// Looks up environment variables.
+    var: fn(&OsStr) -> Option<OsString>,
 }
 
 impl<'a> Expand<'a> {
+    /// Returns an expander that formats `%` components using today's date.
     #[must_use]
     pub fn with_home(home: &'a Path) -> Self {
-        Self { home }
+        let date = chrono::Local::now().date_naive();
+        Self {
+            home,
+            date,
+            var: |name| env::var_os(name),
+        }
     }
 
-    fn special<'b>(&self, s: &OsStr) -> Result<Option<Expansion<'a, 'b>>> {
-        let bytes = s.as_bytes();
+    /// Formats `%` components using `date` instead of today's date.
+    #[must_use]
+    pub fn with_date(self, date: NaiveDate) -> Self {
+        Self { date, ..self }
+    }
+
+    /// Looks up variables using `var` instead of the environment.
+    #[cfg(test)]
+    fn with_var(self, var: fn(&OsStr) -> Option<OsString>) -> Self {
+        Self { var, ..self }
+    }
 
-        // Handle $ at byte level to support non-UTF-8 variable names.
-        if bytes.first() == Some(&b'$') && bytes.len() > 1 {
-            let var = OsStr::from_bytes(&bytes[1..]);
-            let part = env::var_os(var).ok_or(Error::Unset)?;
-            return Ok(Some(Expansion::PathBuf(part.into())));
+    /// Expands a leading `~` or `~user` in `s` to a home directory.
+    fn tilde(&self, s: &[u8]) -> Result<PathBuf> {
+        match s {
+            [b'~'] => Ok(self.home.to_owned()),
+            [b'~', user @ ..] => user_home(user),
+            _ => Ok(PathBuf::from(OsStr::from_bytes(s))),
         }
+    }
 
-        // Other special expansions (%, ~) require UTF-8 values.
-        //
-        // TODO: Support non-UTF-8 values in `~` expansion.
-        let Some(s) = s.to_str() else {
-            return Ok(None);
-        };
-        Ok(if s.starts_with('%') {
-            let today = chrono::Local::now().date_naive();
-            Some(Expansion::String(today.format(s).to_string()))
-        } else if s == "~" {
-            Some(Expansion::Path(self.home))
-        } else {
-            None
-        })
-    }
-
-    fn component<'b>(&self, part: Component<'b>) -> Result<Expansion<'a, 'b>> {
-        // The input may be a "normal" path component (as opposed to, say, a
-        // Windows drive specifier), yet be subject to "special" expansion;
-        // e.g., `~` or `$VARIABLE`.
-        if let Component::Normal(normal) = part
-            && let Some(special) = self.special(normal)?
-        {
-            Ok(special)
-        } else {
-            Ok(Expansion::Component(part))
+    /// Expands a leading `~` in `word`, followed by any variables.
+    fn word(&self, word: &[u8]) -> Result<Vec<u8>> {
+        if word.first() != Some(&b'~') {
+            return self.variables(word);
         }
+        let end = word.iter().position(|&b| b == b'/').unwrap_or(word.len());
+        let mut expanded = self.tilde(&word[..end])?.into_os_string().into_vec();
+        expanded.extend(self.variables(&word[end..])?);
+        Ok(expanded)
     }
 
-    /// Expands a path by substituting `~`, `$VAR`, and `%date` patterns.
-    ///
-    /// # Panics
+    /// Expands each `$VAR`, `${VAR}`, and `${VAR:-default}` in `s`. The default
+    /// is used if the variable is unset or empty, and is itself expanded. A `$`
+    /// followed by anything else is left alone.
+    fn variables(&self, s: &[u8]) -> Result<Vec<u8>> {
+        let mut expanded = Vec::new();
+        let mut rest = s;
+        while let Some(dollar) = rest.iter().position(|&b| b == b'$') {
+            expanded.extend_from_slice(&rest[..dollar]);
+            let after = &rest[dollar + 1..];
+            let (name, default, len) = if let Some(body) = after.strip_prefix(b"{")
+                && let Some(close) = closing_brace(body)
+            {
+                let body = &body[..close];
+                let (name, default) = match body.windows(2).position(|w| w == b":-") {
+                    Some(i) => (&body[..i], Some(&body[i + 2..])),
+                    None => (body, None),
+                };
+                (name, default, close + 2)
+            } else {
+                let len = name_len(after);
+                (&after[..len], None, len)
+            };
+            if name.is_empty() || name_len(name) != name.len() {
+                expanded.push(b'$');
+                rest = after;
+                continue;
+            }
+            let value = (self.var)(OsStr::from_bytes(name));
+            match (value, default) {
+                (Some(value), None) => expanded.extend(value.into_vec()),
+                (Some(value), Some(_)) if !value.is_empty() => expanded.extend(value.into_vec()),
+                (_, Some(default)) => expanded.extend(self.word(default)?),
+                (None, None) => {
+                    return Err(Error::Unset(String::from_utf8_lossy(name).into_owned()));
+                }
+            }
+            rest = &after[len..];
+        }
+        expanded.extend_from_slice(rest);
+        Ok(expanded)
+    }
+
+    /// Expands a path by substituting `~`, `~user`, `%date` patterns, and
+    /// environment variables.
     ///
-    /// Panics if a component begins with `%`, but is not a valid `strftime`
-    /// format string. The panic is because the underlying
-    /// [`chrono::NaiveDate::format`] works lazily, with the actual
-    /// formatting done by [`chrono::format::DelayedFormat::to_string`], which
-    /// has no good way to report an error.
+    /// A leading `~` or `~user` expands to a home directory. A component
+    /// starting with `%` is a `strftime` format string for the date. Elsewhere,
+    /// `$VAR` and `${VAR}` expand to the value of the environment variable
+    /// `VAR`, and `${VAR:-default}` to `default` if `VAR` is unset or empty.
+    /// Variables may appear anywhere within a component; e.g., `proj-$USER`.
+    /// Other `.` components are dropped, except a leading one.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::Empty`] if the path is empty after expansion, or
-    /// [`Error::Unset`] if an environment variable is not set.
-    ///
-    /// # TODO
-    ///
-    /// Avoid the panic. See the [`chrono` implementation][1].
-    ///
-    /// [1]: https://docs.rs/chrono/latest/src/chrono/format/formatting.rs.html#335
+    /// Returns [`Error::Empty`] if the path is empty after expansion,
+    /// [`Error::Unset`] if an environment variable without a default is not
+    /// set, [`Error::User`] if `~user` names an unknown user, or
+    /// [`Error::Strftime`] if a `%` component is not a valid format string.
+    /// (The database rejects such components when it loads, so the last
+    /// should arise only for values from elsewhere.)
     pub fn path(&self, path: &Path) -> Result<PathBuf> {
-        let parts = path
-            .components()
-            .map(|c| self.component(c))
-            .collect::<Result<Vec<_>>>()?;
-        if parts.is_empty() {
+        let bytes = path.as_os_str().as_bytes();
+        let mut expanded = PathBuf::new();
+        if bytes.starts_with(b"/") {
+            expanded.push("/");
+        }
+        for (index, segment) in segments(bytes).into_iter().enumerate() {
+            if index == 0 && segment == b"." {
+                expanded.push(".");
+                continue;
+            }
+            if segment.is_empty() || segment == b"." {
+                continue;
+            }
+            if index == 0 && segment.starts_with(b"~") {
+                expanded.push(self.tilde(segment)?);
+            } else if segment.starts_with(b"%")
+                && let Ok(format) = std::str::from_utf8(segment)
+            {
+                // Check the format up front: `DelayedFormat`'s `Display` impl
+                // reports invalid items as `fmt::Error`, so `to_string` would
+                // panic.
+                let items = StrftimeItems::new(format).collect::<Vec<_>>();
+                if items.contains(&Item::Error) {
+                    return Err(Error::Strftime(format.to_owned()));
+                }
+                let formatted = self.date.format_with_items(items.into_iter());
+                expanded.push(formatted.to_string());
+            } else {
+                expanded.push(OsString::from_vec(self.variables(segment)?));
+            }
+        }
+        if expanded.as_os_str().is_empty() {
             return Err(Error::Empty);
         }
-        Ok(parts.iter().map(AsRef::as_ref).collect())
+        Ok(expanded)
     }
 
     /// Expands the value to an inferred target type.
     ///
-    /// # Panics
-    ///
-    /// Panics if value is inferred to be a path containing an invalid
-    /// `strftime` format string; see [`Self::path`].
-    ///
     /// # Errors
     ///
-    /// Returns [`Error::Empty`] if `value` is empty. Returns [`Error::Unset`]
-    /// if value is inferred to be a path, and contains any unset environment
-    /// variable; e.g., `/$NONESUCH`.
+    /// Returns [`Error::Empty`] if `value` is empty. If value is inferred to
+    /// be a path, returns any error from [`Self::path`]; e.g., [`Error::Unset`]
+    /// for `/$NONESUCH`.
     pub fn target(&self, value: &str) -> Result<Target> {
         if value.is_empty() {
             Err(Error::Empty)
-        } else if value.starts_with(['/', '~', '$', '%']) {
+        } else if is_path(value) {
             Ok(Target::Path(self.path(Path::new(value))?))
+        } else if is_url(value) {
+            Ok(Target::Url(value.to_owned()))
         } else {
             Ok(Target::String(value.to_owned()))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strftime_components() {
+        assert_eq!(invalid_strftime("~/log/%Y/%m/%d"), None);
+        assert_eq!(invalid_strftime("~/log/%Y/%Q"), Some("%Q"));
+        assert_eq!(invalid_strftime("log/%Q"), Some("%Q"));
+    }
+
+    /// Looks up `NAME`, which is `jump`, and `EMPTY`, which is empty.
+    fn var(name: &OsStr) -> Option<OsString> {
+        match name.as_bytes() {
+            b"NAME" => Some("jump".into()),
+            b"EMPTY" => Some(OsString::new()),
+            _ => None,
+        }
+    }
+
+    fn expand(value: &str) -> Result<String> {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 27).unwrap();
+        let expand = Expand::with_home(Path::new("/home/user"))
+            .with_date(date)
+            .with_var(var);
+        let path = expand.path(Path::new(value))?;
+        Ok(path.display().to_string())
+    }
+
+    #[test]
+    fn home() {
+        assert_eq!(expand("~").unwrap(), "/home/user");
+        assert_eq!(expand("~/conf").unwrap(), "/home/user/conf");
+        assert_eq!(expand("/x/~").unwrap(), "/x/~");
+    }
+
+    #[test]
+    fn other_users() {
+        assert!(expand("~root/x").unwrap().ends_with("/x"));
+        assert!(expand("~root").unwrap().starts_with('/'));
+        let err = expand("~nonesuch-jump-user/x").unwrap_err();
+        assert!(matches!(err, Error::User(name) if name == "nonesuch-jump-user"));
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(expand("/$NAME/x").unwrap(), "/jump/x");
+        assert_eq!(expand("/proj-$NAME").unwrap(), "/proj-jump");
+        assert_eq!(expand("/${NAME}s").unwrap(), "/jumps");
+        assert_eq!(expand("/$NAME.d").unwrap(), "/jump.d");
+        assert_eq!(expand("/a$/b${}/$1").unwrap(), "/a$/b${}/$1");
+    }
+
+    #[test]
+    fn defaults() {
+        let unset = "${NONESUCH_JUMP_VAR:-~}/git";
+        assert_eq!(expand(unset).unwrap(), "/home/user/git");
+        let nested = "/${NONESUCH_JUMP_VAR:-${NAME}/x}/y";
+        assert_eq!(expand(nested).unwrap(), "/jump/x/y");
+        let set = "/${NAME:-other}";
+        assert_eq!(expand(set).unwrap(), "/jump");
+        assert_eq!(expand("/${EMPTY:-other}").unwrap(), "/other");
+        assert_eq!(expand("/x$EMPTY").unwrap(), "/x");
+    }
+
+    #[test]
+    fn unset_variables_are_named() {
+        let err = expand("/x-$NONESUCH_JUMP_VAR").unwrap_err();
+        assert!(matches!(&err, Error::Unset(name) if name == "NONESUCH_JUMP_VAR"));
+        assert_eq!(err.to_string(), "Unset variable: NONESUCH_JUMP_VAR");
+        assert!(matches!(
+            expand("/${NONESUCH_JUMP_VAR}"),
+            Err(Error::Unset(_))
+        ));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            expand("~/log/%Y/%m/%d").unwrap(),
+            "/home/user/log/2025/03/27"
+        );
+        let err = expand("~/log/%Y/%Q").unwrap_err();
+        assert!(matches!(err, Error::Strftime(format) if format == "%Q"));
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(expand(".").unwrap(), ".");
+        assert_eq!(expand("./x/./y").unwrap(), "./x/y");
+        assert_eq!(expand("x/%Y").unwrap(), "x/2025");
+    }
+
+    #[test]
+    fn inferred_types() {
+        let expand = Expand::with_home(Path::new("/home/user"));
+        let url = expand.target("https://example.com").unwrap();
+        assert!(matches!(url, Target::Url(url) if url == "https://example.com"));
+        let other = expand.target("mailto:jeff@example.com").unwrap();
+        assert!(matches!(other, Target::String(_)));
+        assert!(matches!(expand.target("~"), Ok(Target::Path(_))));
+    }
+
+    #[test]
+    fn non_utf8() {
+        let path = Path::new(OsStr::from_bytes(b"~/caf\xe9"));
+        let expand = Expand::with_home(Path::new("/home/user"));
+        let expanded = expand.path(path).unwrap();
+        assert_eq!(expanded.as_os_str().as_bytes(), b"/home/user/caf\xe9");
+    }
+}
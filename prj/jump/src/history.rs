@@ -0,0 +1,283 @@
+//! A frecency-ranked history of resolved paths, consulted when a name is not
+//! in any `jump.yaml`.
+//!
+//! Ranking follows zoxide: each resolution adds one to a path's rank, which is
+//! weighted by how recently the path was last resolved. Once the ranks add up
+//! to more than [`MAX_RANK`], they all decay, and paths whose rank falls below
+//! one are dropped.
+//!
+//! A query matches a path if its keywords appear in the path in order, and
+//! the last keyword appears in the path's last component. Keywords without
+//! uppercase letters match case-insensitively.
+
+use std::ffi::OsStr;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// The total rank above which every rank decays.
+const MAX_RANK: f64 = 10_000.0;
+
+#[derive(Debug)]
+pub enum Error {
+    /// History is disabled by the environment.
+    Disabled,
+    /// The history file could not be read or written.
+    Io(PathBuf, io::Error),
+    /// A path to be forgotten was not in the history.
+    Unknown(PathBuf),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "history is disabled by {DISABLE_VAR}"),
+            Self::Io(path, e) => write!(f, "{}: {e}", path.display()),
+            Self::Unknown(path) => write!(f, "{}: not in history", path.display()),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The environment variable that, if set to a non-empty value, disables
+/// history.
+pub const DISABLE_VAR: &str = "JUMP_NO_HISTORY";
+
+struct Visit {
+    path: PathBuf,
+    rank: f64,
+    /// When the path was last resolved, in seconds since the Unix epoch.
+    last: u64,
+}
+
+impl Visit {
+    fn parse(line: &[u8]) -> Option<Self> {
+        let mut fields = line.splitn(3, |&b| b == b'\t');
+        let rank = std::str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+        let last = std::str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+        let path = PathBuf::from(OsStr::from_bytes(fields.next()?));
+        Some(Visit { path, rank, last })
+    }
+
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// Returns true if `keywords` appear in `path` in order, with the last one in
+/// the final component.
+fn matches(path: &Path, keywords: &[&str]) -> bool {
+    let Some(last) = keywords.last() else {
+        return false;
+    };
+    let fold = keywords.iter().all(|k| !k.chars().any(char::is_uppercase));
+    let fold = |s: &str| if fold { s.to_lowercase() } else { s.to_owned() };
+    let haystack = fold(&path.to_string_lossy());
+    let mut rest = haystack.as_str();
+    for keyword in &keywords[..keywords.len() - 1] {
+        let keyword = fold(keyword);
+        match rest.find(&keyword) {
+            Some(i) => rest = &rest[i + keyword.len()..],
+            None => return false,
+        }
+    }
+    let name = rest.rsplit('/').next().unwrap_or(rest);
+    !last.contains('/') && name.contains(&fold(last))
+}
+
+/// Paths resolved in the past, ranked by frecency.
+pub struct History {
+    file: PathBuf,
+    visits: Vec<Visit>,
+    /// The current time, in seconds since the Unix epoch.
+    now: u64,
+}
+
+impl History {
+    /// Reads the history from `file`, which need not exist. Malformed lines
+    /// are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read.
+    pub fn load(file: PathBuf, now: u64) -> Result<Self> {
+        let bytes = match fs::read(&file) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(Error::Io(file, e)),
+        };
+        let visits = bytes
+            .split(|&b| b == b'\n')
+            .filter_map(Visit::parse)
+            .collect();
+        Ok(History { file, visits, now })
+    }
+
+    /// Writes the history back to its file, creating the parent directory if
+    /// necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> Result<()> {
+        let mut bytes = Vec::new();
+        for visit in &self.visits {
+            write!(bytes, "{}\t{}\t", visit.rank, visit.last).expect("vectors are writable");
+            bytes.extend_from_slice(visit.path.as_os_str().as_bytes());
+            bytes.push(b'\n');
+        }
+        // Write a temporary file and rename it, so that concurrent jumps never
+        // see a partial history.
+        let temp = self.file.with_extension("tmp");
+        let io = |e| Error::Io(self.file.clone(), e);
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir).map_err(io)?;
+        }
+        fs::write(&temp, bytes).map_err(io)?;
+        fs::rename(&temp, &self.file).map_err(io)
+    }
+
+    /// Records a resolution of `path`.
+    pub fn record(&mut self, path: &Path) {
+        match self.visits.iter_mut().find(|v| v.path == path) {
+            Some(visit) => {
+                visit.rank += 1.0;
+                visit.last = self.now;
+            }
+            None => self.visits.push(Visit {
+                path: path.to_owned(),
+                rank: 1.0,
+                last: self.now,
+            }),
+        }
+        if self.visits.iter().map(|v| v.rank).sum::<f64>() > MAX_RANK {
+            for visit in &mut self.visits {
+                visit.rank *= 0.9;
+            }
+            self.visits.retain(|v| v.rank >= 1.0);
+        }
+    }
+
+    /// Removes `path` from the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unknown`] if the path is not in the history.
+    pub fn forget(&mut self, path: &Path) -> Result<()> {
+        let len = self.visits.len();
+        self.visits.retain(|v| v.path != path);
+        if self.visits.len() == len {
+            return Err(Error::Unknown(path.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Returns the existing directory with the highest frecency that matches
+    /// `keywords`.
+    #[must_use]
+    pub fn best(&self, keywords: &[&str]) -> Option<&Path> {
+        self.visits
+            .iter()
+            .filter(|v| matches(&v.path, keywords) && v.path.is_dir())
+            .max_by(|a, b| a.score(self.now).total_cmp(&b.score(self.now)))
+            .map(|v| v.path.as_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords() {
+        let path = Path::new("/home/user/prj/jump/src");
+        assert!(matches(path, &["src"]));
+        assert!(matches(path, &["jump", "src"]));
+        assert!(!matches(path, &["SRC"]));
+        assert!(!matches(path, &["Sr"]));
+        assert!(matches(Path::new("/home/user/Src"), &["src"]));
+        assert!(!matches(path, &["jump"]));
+        assert!(!matches(path, &["src", "jump"]));
+        assert!(!matches(path, &[]));
+    }
+
+    #[test]
+    fn frecency() {
+        let dir = tempfile::tempdir().unwrap();
+        let (old, new) = (dir.path().join("old/src"), dir.path().join("new/src"));
+        fs::create_dir_all(&old).unwrap();
+        fs::create_dir_all(&new).unwrap();
+        let file = dir.path().join("history");
+
+        let mut history = History::load(file.clone(), 0).unwrap();
+        for _ in 0..3 {
+            history.record(&old);
+        }
+        history.save().unwrap();
+
+        // Three visits an hour ago outrank one visit now, but not two.
+        let mut history = History::load(file.clone(), HOUR).unwrap();
+        history.record(&new);
+        assert_eq!(history.best(&["src"]), Some(old.as_path()));
+        history.record(&new);
+        assert_eq!(history.best(&["src"]), Some(new.as_path()));
+        assert_eq!(history.best(&["old", "src"]), Some(old.as_path()));
+        assert_eq!(history.best(&["xyzzy"]), None);
+    }
+
+    #[test]
+    fn missing_directories_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::load(dir.path().join("history"), 0).unwrap();
+        history.record(&dir.path().join("gone"));
+        assert_eq!(history.best(&["gone"]), None);
+    }
+
+    #[test]
+    fn forget() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state/history");
+        let mut history = History::load(file.clone(), 0).unwrap();
+        history.record(dir.path());
+        history.save().unwrap();
+
+        let mut history = History::load(file, 0).unwrap();
+        assert_eq!(history.best(&[]), None);
+        history.forget(dir.path()).unwrap();
+        assert!(matches!(history.forget(dir.path()), Err(Error::Unknown(_))));
+    }
+
+    #[test]
+    fn decay() {
+        let mut history = History::load(PathBuf::from("/nonexistent"), 0).unwrap();
+        history.visits.push(Visit {
+            path: PathBuf::from("/rare"),
+            rank: 1.0,
+            last: 0,
+        });
+        history.visits.push(Visit {
+            path: PathBuf::from("/common"),
+            rank: MAX_RANK,
+            last: 0,
+        });
+        history.record(Path::new("/common"));
+        let paths = history.visits.iter().map(|v| &v.path).collect::<Vec<_>>();
+        assert_eq!(paths, [Path::new("/common")]);
+    }
+}
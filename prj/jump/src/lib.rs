@@ -1,22 +1,58 @@
+mod builtin;
+mod date;
+mod edit;
 mod error;
 mod expansion;
+mod history;
+mod suggest;
+mod template;
 
 pub mod db;
 
 use std::env;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-pub use db::Database;
+use chrono::NaiveDate;
+
+use builtin::Builtin;
+use history::History;
+
+pub use db::{Database, Entry, Kind};
 pub use error::Error;
 pub use expansion::{Expand, Target};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The maximum number of similar names listed when a target is not found.
+const SUGGESTIONS: usize = 3;
+
 /// Returns `$XDG_CONFIG_HOME` if set, and `~/.config` otherwise.
 fn config_home(home: &Path) -> PathBuf {
     env::var_os("XDG_CONFIG_HOME").map_or_else(|| home.join(".config"), PathBuf::from)
 }
 
+/// Returns `$XDG_STATE_HOME` if set, and `~/.local/state` otherwise.
+fn state_home(home: &Path) -> PathBuf {
+    env::var_os("XDG_STATE_HOME").map_or_else(|| home.join(".local/state"), PathBuf::from)
+}
+
+/// Returns the history, unless it is disabled by the environment or
+/// cannot be read, in which case a warning is printed.
+fn history_from_env(home: &Path) -> Option<History> {
+    if env::var_os(history::DISABLE_VAR).is_some_and(|v| !v.is_empty()) {
+        return None;
+    }
+    let file = state_home(home).join("jump/history");
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // The history is only a fallback, so a broken one mustn't stop lookups.
+    History::load(file, now)
+        .inspect_err(|err| eprintln!("warning: {err}; continuing without history"))
+        .ok()
+}
+
 fn dirs_from_env(home: &Path) -> Vec<PathBuf> {
     let string = env::var_os("JUMP_DIRS").unwrap_or_default();
     let paths = env::split_paths(&string)
@@ -28,16 +64,45 @@ fn dirs_from_env(home: &Path) -> Vec<PathBuf> {
     paths
 }
 
-/// Returns the accumulated database, and a list of paths loaded (for use in
-/// error messages).
-fn db_from_env(home: &Path) -> Result<(Database, Vec<PathBuf>)> {
+/// The name of a project's own target file.
+const LOCAL_FILE: &str = ".jump.yaml";
+
+/// Returns the nearest [`LOCAL_FILE`] in `dir` or its ancestors.
+fn local_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(LOCAL_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Reads the project file `local` into `db`, returning false if it cannot be
+/// read or parsed. A broken `.jump.yaml` in some clone is only warned about,
+/// so that it doesn't stop every jump from working.
+fn read_local(db: &mut Database, local: &Path) -> bool {
+    match db.read_local(local) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("warning: {err}; skipping it");
+            false
+        }
+    }
+}
+
+/// Returns the accumulated database, a list of paths loaded (for use in error
+/// messages), and the local file loaded after them, if any.
+fn db_from_env(
+    home: &Path,
+    cwd: Option<&Path>,
+) -> Result<(Database, Vec<PathBuf>, Option<PathBuf>)> {
     let dirs = dirs_from_env(home);
     let paths = dirs.iter().map(|p| p.join("jump.yaml")).collect::<Vec<_>>();
     let mut db = Database::new();
     for path in &paths {
         db.read_file(path)?;
     }
-    Ok((db, paths))
+    let local = cwd
+        .and_then(local_file)
+        .filter(|local| read_local(&mut db, local));
+    Ok((db, paths, local))
 }
 
 /// Maps target names to paths from a [`Database`].
@@ -45,6 +110,21 @@ pub struct App {
     home: PathBuf,
     db: Database,
     db_paths: Vec<PathBuf>,
+    /// The nearest `.jump.yaml` in the current directory or its ancestors,
+    /// which is read after [`Self::db_paths`].
+    local: Option<PathBuf>,
+    /// The directory built-in targets such as `@root` are relative to.
+    cwd: Option<PathBuf>,
+    /// The date relative dates are relative to.
+    today: NaiveDate,
+    /// Whether unknown names resolve to a clearly closest match.
+    fuzzy: bool,
+    /// Whether resolving a path creates it, if it does not exist.
+    mkdir: bool,
+    /// Whether resolving a path fails, if it does not exist.
+    must_exist: bool,
+    /// Previously resolved paths, or [`None`] if history is disabled.
+    history: Option<History>,
 }
 
 impl App {
@@ -53,52 +133,1355 @@ impl App {
     /// empty or unset, reads from `$XDG_CONFIG_HOME/jump.yaml` (defaulting to
     /// `~/.config/jump.yaml`).
     ///
+    /// The app then reads the nearest `.jump.yaml` in the current directory or
+    /// its ancestors, if any, whose names take precedence. See
+    /// [`Database::read_local`].
+    ///
+    /// Unless `$JUMP_NO_HISTORY` is set to a non-empty value, the app also
+    /// reads the history of resolved paths from `$XDG_STATE_HOME/jump/history`
+    /// (defaulting to `~/.local/state/jump/history`).
+    ///
     /// # Panics
     ///
     /// Panics if [`env::home_dir`] returns [`Err`].
     ///
     /// # Errors
     ///
-    /// Returns [`Err`] if the target database cannot be read.
+    /// Returns [`Err`] if the target database cannot be read.  An unreadable
+    /// history is only warned about.
     pub fn from_env() -> Result<App> {
         let home = env::home_dir().expect("user should have a home directory");
-        let (db, db_paths) = db_from_env(&home)?;
-        Ok(App { home, db, db_paths })
+        let cwd = env::current_dir().ok();
+        let (db, db_paths, local) = db_from_env(&home, cwd.as_deref())?;
+        let today = chrono::Local::now().date_naive();
+        let history = history_from_env(&home);
+        Ok(App {
+            home,
+            db,
+            db_paths,
+            local,
+            cwd,
+            today,
+            fuzzy: false,
+            mkdir: false,
+            must_exist: false,
+            history,
+        })
+    }
+
+    /// Returns the database merged from every loaded `jump.yaml`.
+    #[must_use]
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// If `fuzzy` is true, a target name that is not found resolves to the
+    /// most similar name instead, as long as that name is clearly closer than
+    /// any other.
+    #[must_use]
+    pub fn with_fuzzy(self, fuzzy: bool) -> Self {
+        Self { fuzzy, ..self }
     }
 
+    /// If `mkdir` is true, resolving a path that does not exist creates it as
+    /// a directory, along with any missing parents, as if every path target
+    /// had `mkdir: true`.
+    #[must_use]
+    pub fn with_mkdir(self, mkdir: bool) -> Self {
+        Self { mkdir, ..self }
+    }
+
+    /// If `must_exist` is true, resolving a path that does not exist (and is
+    /// not to be created) fails.
+    #[must_use]
+    pub fn with_must_exist(self, must_exist: bool) -> Self {
+        Self { must_exist, ..self }
+    }
+
+    /// Returns the name and entry of the named target, or of the only target
+    /// whose name starts with `target`. Exact matches take precedence over prefixes.
+    ///
     /// # Errors
     ///
-    /// Returns [`Error::Target`] if the target is not in this app's database.
-    fn target(&self, target: &str) -> Result<&String> {
-        self.db.get(target).ok_or_else(|| Error::Target {
-            name: target.to_owned(),
-            searched: self.db_paths.clone(),
-        })
+    /// Returns [`Error::Target`] if no name in this app's database matches the
+    /// target, or [`Error::Ambiguous`] if more than one name starts with it.
+    fn target(&self, target: &str) -> Result<(&str, &Entry)> {
+        self.find(target)
+            .unwrap_or_else(|| Err(self.not_found(target)))
+    }
+
+    /// Like [`Self::target`], but returns [`None`] rather than building an
+    /// [`Error::Target`], whose suggestions take a scan of the database.
+    fn find(&self, target: &str) -> Option<Result<(&str, &Entry)>> {
+        if let Some(named) = self.db.named(target) {
+            return Some(Ok(named));
+        }
+        let mut matches = self.db.prefixed(target).collect::<Vec<_>>();
+        match matches.as_slice() {
+            [named] if !target.is_empty() => Some(Ok(*named)),
+            [_, _, ..] if !target.is_empty() => {
+                matches.sort_unstable_by_key(|&(name, _)| name);
+                let candidates = matches
+                    .into_iter()
+                    .map(|(name, entry)| (name.to_owned(), entry.location.clone()))
+                    .collect();
+                Some(Err(Error::Ambiguous {
+                    name: target.to_owned(),
+                    candidates,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    fn not_found(&self, name: &str) -> Error {
+        Error::Target {
+            name: name.to_owned(),
+            searched: self.files().cloned().collect(),
+            suggestions: suggest::ranked(&self.db, name)
+                .into_iter()
+                .take(SUGGESTIONS)
+                .map(|m| (m.name.to_owned(), m.entry.value.clone()))
+                .collect(),
+        }
     }
 
     /// Looks up the specified target in this app's database and resolves it
     /// to a [`Target`] value.
     ///
-    /// The resolved value depends on the target type:
-    /// - URLs (`http://`, `https://`) → `Target::String` (verbatim)
+    /// The resolved value depends on the target type, which typed targets
+    /// state, and which is otherwise inferred from the value:
+    /// - URLs (`http://`, `https://`) → `Target::Url` (verbatim)
     /// - Paths (`/`, `~`, `$`, `%`) → `Target::Path` (expanded)
     /// - Everything else → `Target::String` (verbatim)
     ///
+    /// A relative typed path is relative to the directory of the file that
+    /// defines it. A typed command takes `args` as further arguments, rather
+    /// than filling placeholders.
+    ///
+    /// If no name matches exactly, but exactly one name starts with `target`,
+    /// that name is used instead; e.g., `co` resolves to `conf`.
+    ///
     /// If the target is not found, but ends with a slash character (`'/'`),
     /// lookup is also attempted without the trailing slash, in case the user's
     /// shell tab-completed a directory that happened to have the same name as
     /// the target.
     ///
+    /// If the target is still not found, but contains a slash, the part before
+    /// the first slash is resolved as a target name, and the rest is joined
+    /// onto the resulting path; e.g., `conf/prj/jump` resolves to
+    /// `~/conf/prj/jump`. The joined path must exist.
+    ///
+    /// If the target is still not found, and this app is fuzzy (see
+    /// [`Self::with_fuzzy`]), the most similar name is used instead.
+    ///
+    /// Failing all that, a nonempty target and `args` are treated as keywords,
+    /// and matched against the history of resolved paths (see
+    /// [`Self::record`]). The matching directory with the highest frecency is
+    /// returned.
+    ///
+    /// The value may be a template containing placeholders, such as `{1}` or
+    /// `{owner}`, which are filled from `args` before expansion. Positional
+    /// arguments fill `{1}`, `{2}`, and so on; arguments of the form
+    /// `name=value` fill `{name}`.
+    ///
+    /// A value starting with `@` refers to another target: `@conf/prj` expands
+    /// the target `conf`, and appends `prj`. A reference to a target that
+    /// refers back, directly or not, is an error. A `target` starting with `@`
+    /// is resolved the same way.
+    ///
+    /// The references `@root`, `@crate`, and `@main` are built in, and take
+    /// precedence over any targets of those names. They stand for the root
+    /// of the jj or git workspace containing the current directory, the
+    /// nearest directory holding a `Cargo.toml`, and the default workspace of
+    /// the current jj repository.
+    ///
+    /// A URL may contain a query placeholder, `%s`, which is filled with any
+    /// arguments not used by other placeholders, joined by spaces and
+    /// percent-encoded; e.g., `jump docs Vec::retain`.
+    ///
+    /// If a path contains `%`, the first argument that is a relative date,
+    /// such as `-1`, `yesterday`, `last-monday`, or `2025-03-27`, sets the date
+    /// used to format `%` components, rather than filling a placeholder.
+    ///
+    /// If the target is a path that does not exist, and either it has
+    /// `mkdir: true` or this app is set to create paths (see
+    /// [`Self::with_mkdir`]), it is created as a directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the target cannot be found or resolved, or if `args`
+    /// do not fit its placeholders. Returns [`Error::Builtin`] if a built-in
+    /// target does not apply to the current directory. Returns
+    /// [`Error::Mkdir`] if a directory cannot be created, and
+    /// [`Error::Nonexistent`] if the path does not exist and this app requires
+    /// it to (see [`Self::with_must_exist`]).
+    pub fn resolve(&self, target: &str, args: &[String]) -> Result<Target> {
+        self.resolve_named(target, args).map(|(_, target)| target)
+    }
+
+    /// Like [`Self::resolve`], but also returns the name of the target that
+    /// was resolved, or [`None`] if the path came from the history or a
+    /// reference such as `@root`.
+    ///
     /// # Errors
     ///
-    /// Returns [`Err`] if the target cannot be found or resolved.
-    pub fn resolve(&self, target: &str) -> Result<Target> {
-        let value = self.target(target).or_else(|err| {
+    /// Returns the same errors as [`Self::resolve`].
+    pub fn resolve_named(&self, target: &str, args: &[String]) -> Result<(Option<&str>, Target)> {
+        if let Some(reference) = target.strip_prefix('@') {
+            // A reference has no placeholders, and so takes no arguments.
+            let reference = template::fill(reference, args)?;
+            let expand = Expand::with_home(&self.home).with_date(self.today);
+            let target = self.reference(&expand, target, &reference, &mut Vec::new())?;
+            self.ensure(&target, false)?;
+            return Ok((None, target));
+        }
+        let trimmed = target.strip_suffix('/');
+        let name = trimmed.unwrap_or(target);
+        let entry = self
+            .find(target)
+            .or_else(|| self.find(trimmed?))
+            .unwrap_or_else(|| Err(self.not_found(target)));
+        if matches!(entry, Err(Error::Target { .. }))
+            && let Some((prefix, rest)) = name.split_once('/')
+        {
+            match self.or_fuzzy(prefix, self.target(prefix)) {
+                Ok((prefix, entry)) => {
+                    let target = self.subpath(name, prefix, entry, rest, args)?;
+                    return Ok((Some(prefix), target));
+                }
+                Err(err @ Error::Ambiguous { .. }) => return Err(err),
+                Err(_) => (),
+            }
+        }
+        let (name, entry) = match self.or_fuzzy(name, entry) {
+            // An empty keyword would match every directory in the history.
+            Err(err @ Error::Target { .. }) if !name.is_empty() => {
+                let keywords = std::iter::once(name)
+                    .chain(args.iter().map(String::as_str))
+                    .collect::<Vec<_>>();
+                return self
+                    .history
+                    .as_ref()
+                    .and_then(|history| history.best(&keywords))
+                    .map(|path| (None, Target::Path(path.to_owned())))
+                    .ok_or(err);
+            }
+            entry => entry?,
+        };
+        let target = self.expand(name, entry, args)?;
+        self.ensure(&target, entry.mkdir)?;
+        Ok((Some(name), target))
+    }
+
+    /// If `target` is a path that does not exist, creates it if either `mkdir`
+    /// or this app says to, and otherwise fails if this app says to.
+    fn ensure(&self, target: &Target, mkdir: bool) -> Result<()> {
+        if let Target::Path(path) = target
+            && !path.exists()
+        {
+            if mkdir || self.mkdir {
+                std::fs::create_dir_all(path).map_err(|e| Error::Mkdir(path.clone(), e))?;
+            } else if self.must_exist {
+                return Err(Error::Nonexistent(path.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the directory `builtin` stands for.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Builtin`] if there is no such directory.
+    fn builtin(&self, builtin: Builtin) -> Result<PathBuf> {
+        self.cwd
+            .as_deref()
+            .and_then(|cwd| builtin.resolve(cwd))
+            .ok_or(Error::Builtin(builtin))
+    }
+
+    /// Returns the definition of `name` in the nearest `.jump.yaml` if it
+    /// shadows a definition in some `jump.yaml`.
+    #[must_use]
+    pub fn shadowed(&self, name: &str) -> Option<&db::Conflict> {
+        self.db
+            .shadows()
+            .iter()
+            .rev()
+            .find(|conflict| conflict.name == name)
+    }
+
+    /// If this app is fuzzy, and `result` is [`Error::Target`], returns the
+    /// name most similar to `name`, if any, and its entry.
+    fn or_fuzzy<'a>(
+        &'a self,
+        name: &str,
+        result: Result<(&'a str, &'a Entry)>,
+    ) -> Result<(&'a str, &'a Entry)> {
+        match result {
+            Err(err @ Error::Target { .. }) if self.fuzzy => suggest::best(&self.db, name)
+                .map(|m| (m.name, m.entry))
+                .ok_or(err),
+            result => result,
+        }
+    }
+
+    /// Resolves `entry`, which the leading `prefix` of `name` matched, and
+    /// joins the `rest` of `name` onto the resulting path.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Subpath`] if the joined path does not exist, and
+    /// [`Error::Target`] if `entry` is not a path.
+    fn subpath(
+        &self,
+        name: &str,
+        prefix: &str,
+        entry: &Entry,
+        rest: &str,
+        args: &[String],
+    ) -> Result<Target> {
+        let Target::Path(resolved) = self.expand(prefix, entry, args)? else {
+            return Err(self.not_found(name));
+        };
+        let mut path = resolved.clone();
+        for component in Path::new(rest).components() {
+            let next = path.join(component);
+            if !next.exists() {
+                return Err(Error::Subpath {
+                    name: name.to_owned(),
+                    prefix: prefix.to_owned(),
+                    resolved,
+                    existing: path,
+                    missing: component.as_os_str().to_string_lossy().into_owned(),
+                });
+            }
+            path = next;
+        }
+        Ok(Target::Path(path))
+    }
+
+    /// Fills the placeholders in the value of the target `name`, whose entry is
+    /// `entry`, from `args`, and expands it. A command instead takes `args` as
+    /// further arguments.
+    fn expand(&self, name: &str, entry: &Entry, args: &[String]) -> Result<Target> {
+        if let Kind::Command(argv) = &entry.kind {
+            return Ok(Target::Command(argv.iter().chain(args).cloned().collect()));
+        }
+        let value = &entry.value;
+        let url = match entry.kind {
+            Kind::Inferred => expansion::is_url(value),
+            Kind::Url => true,
+            Kind::Path | Kind::Command(_) | Kind::Snippet => false,
+        };
+        // Only paths are formatted as dates; a `%` elsewhere, such as in a
+        // percent-encoded URL, is literal.
+        let path = match entry.kind {
+            Kind::Inferred => expansion::is_path(value),
+            Kind::Path => true,
+            Kind::Url | Kind::Command(_) | Kind::Snippet => false,
+        };
+        let mut args = args.to_vec();
+        let mut date = self.today;
+        if path
+            && value.contains('%')
+            && let Some((index, arg_date)) = args
+                .iter()
+                .enumerate()
+                .find_map(|(index, arg)| Some((index, date::parse(arg, self.today)?)))
+        {
+            args.remove(index);
+            date = arg_date;
+        }
+        let value = if url && value.contains(template::QUERY) {
+            template::fill_query(value, &args)?
+        } else {
+            template::fill(value, &args)?
+        };
+        let expand = Expand::with_home(&self.home).with_date(date);
+        self.typed(&expand, name, entry, &value, &mut Vec::new())
+    }
+
+    /// Expands `value`, which is the value of the target `name` or a filled
+    /// copy of it, according to the kind of its `entry`. `chain` lists the
+    /// targets whose values refer, in turn, to this one.
+    fn typed(
+        &self,
+        expand: &Expand,
+        name: &str,
+        entry: &Entry,
+        value: &str,
+        chain: &mut Vec<String>,
+    ) -> Result<Target> {
+        if value.is_empty() {
+            return Err(expansion::Error::Empty.into());
+        }
+        if matches!(entry.kind, Kind::Inferred | Kind::Path)
+            && let Some(reference) = value.strip_prefix('@')
+        {
+            return self.reference(expand, name, reference, chain);
+        }
+        let target = match &entry.kind {
+            Kind::Inferred => expand.target(value)?,
+            Kind::Path => {
+                let path = expand.path(Path::new(value))?;
+                match entry.location.file.parent() {
+                    // Joining normalizes away a leading `.` component.
+                    Some(dir) if path.is_relative() => {
+                        Target::Path(dir.join(path).components().collect())
+                    }
+                    _ => Target::Path(path),
+                }
+            }
+            Kind::Url => Target::Url(value.to_owned()),
+            Kind::Command(argv) => Target::Command(argv.clone()),
+            Kind::Snippet => Target::String(value.to_owned()),
+        };
+        Ok(target)
+    }
+
+    /// Expands a `reference` such as `conf/prj` in the value of the target
+    /// `name`: the target before the first slash, with the rest appended.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Cycle`] if the referenced target refers back to
+    /// `name`, directly or not, [`Error::Target`] if it does not exist, and
+    /// [`Error::Reference`] if there is a suffix, but the target is neither a
+    /// path nor a URL. Returns [`Error::Builtin`] if a built-in target does not
+    /// apply to the current directory.
+    fn reference(
+        &self,
+        expand: &Expand,
+        name: &str,
+        reference: &str,
+        chain: &mut Vec<String>,
+    ) -> Result<Target> {
+        let (referenced, suffix) = reference.split_once('/').unwrap_or((reference, ""));
+        let target = if let Some(builtin) = Builtin::parse(referenced) {
+            Target::Path(self.builtin(builtin)?)
+        } else {
+            chain.push(name.to_owned());
+            if chain.iter().any(|n| n == referenced) {
+                chain.push(referenced.to_owned());
+                return Err(Error::Cycle(std::mem::take(chain)));
+            }
+            let entry = self
+                .db
+                .entry(referenced)
+                .ok_or_else(|| self.not_found(referenced))?;
+            let target = self.typed(expand, referenced, entry, &entry.value, chain)?;
+            chain.pop();
             target
-                .strip_suffix('/')
-                .and_then(|target| self.target(target).ok())
-                .ok_or(err)
-        })?;
-        Ok(Expand::with_home(&self.home).target(value)?)
+        };
+        match target {
+            target if suffix.is_empty() => Ok(target),
+            Target::Path(path) => Ok(Target::Path(path.join(expand.path(Path::new(suffix))?))),
+            Target::Url(url) => Ok(Target::Url(format!(
+                "{}/{suffix}",
+                url.trim_end_matches('/')
+            ))),
+            Target::Command(_) | Target::String(_) => Err(Error::Reference {
+                name: name.to_owned(),
+                referenced: referenced.to_owned(),
+            }),
+        }
+    }
+
+    /// Returns each target name starting with `partial`, sorted, along with
+    /// its expanded value. Placeholders in the values are left unfilled.
+    ///
+    /// If `partial` contains a slash, and the part before the last slash
+    /// resolves to a directory, instead returns each subdirectory whose name
+    /// starts with the part after the slash, as `DIR/NAME/`. Hidden
+    /// directories are included only if that part starts with a dot.
+    ///
+    /// If `partial` starts with `@`, and has no slash, returns each built-in
+    /// target whose name starts with it, and that applies to the current
+    /// directory.
+    #[must_use]
+    pub fn complete(&self, partial: &str) -> Vec<(String, Result<Target>)> {
+        if let Some(prefix) = partial.strip_prefix('@')
+            && !prefix.contains('/')
+        {
+            return Builtin::ALL
+                .into_iter()
+                .filter(|builtin| builtin.name().starts_with(prefix))
+                .filter_map(|builtin| {
+                    let path = self.builtin(builtin).ok()?;
+                    Some((builtin.to_string(), Ok(Target::Path(path))))
+                })
+                .collect();
+        }
+        if let Some((dir, leaf)) = partial.rsplit_once('/') {
+            let Ok(Target::Path(path)) = self.resolve(dir, &[]) else {
+                return Vec::new();
+            };
+            let Ok(entries) = std::fs::read_dir(path) else {
+                return Vec::new();
+            };
+            let mut subdirs = entries
+                .filter_map(|entry| {
+                    let entry = entry.ok()?;
+                    let name = entry.file_name().into_string().ok()?;
+                    let hidden = name.starts_with('.') && !leaf.starts_with('.');
+                    let path = entry.path();
+                    (name.starts_with(leaf) && !hidden && path.is_dir())
+                        .then(|| (format!("{dir}/{name}/"), Ok(Target::Path(path))))
+                })
+                .collect::<Vec<_>>();
+            subdirs.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            return subdirs;
+        }
+        self.list_prefixed(partial)
+            .into_iter()
+            .map(|(name, _, target)| (name.to_owned(), target))
+            .collect()
+    }
+
+    /// Returns the name of the target whose path is the nearest ancestor of
+    /// `path` (by default, the current directory), along with the rest of the
+    /// path relative to it. If several names share that path, returns the
+    /// shortest, breaking ties alphabetically. For example, in `~/conf/prj`,
+    /// given `~/conf: [c, conf]`, returns `("c", "prj")`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unnamed`] if no target path contains `path`.
+    pub fn which(&self, path: Option<&Path>) -> Result<(&str, PathBuf)> {
+        let path = path.unwrap_or(Path::new("."));
+        let path = std::path::absolute(path).map_err(|e| db::Error::io(path.into(), e))?;
+        let expand = Expand::with_home(&self.home).with_date(self.today);
+        self.db
+            .iter()
+            .filter_map(|(name, entry)| {
+                let chain = &mut Vec::new();
+                let Ok(Target::Path(target)) =
+                    self.typed(&expand, name, entry, &entry.value, chain)
+                else {
+                    return None;
+                };
+                let suffix = path.strip_prefix(&target).ok()?.to_owned();
+                Some((name, suffix))
+            })
+            .min_by_key(|(name, suffix)| (suffix.components().count(), name.len(), *name))
+            .ok_or(Error::Unnamed(path))
+    }
+
+    /// Returns every target name, sorted, along with its entry and its
+    /// expanded value. Placeholders in the values are left unfilled.
+    #[must_use]
+    pub fn list(&self) -> Vec<(&str, &Entry, Result<Target>)> {
+        self.list_prefixed("")
+    }
+
+    /// Records a resolution of `target`, if it is a path, in the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::History`] if the history file cannot be written.
+    pub fn record(&mut self, target: &Target) -> Result<()> {
+        if let (Some(history), Target::Path(path)) = (&mut self.history, target) {
+            history.record(path);
+            history.save()?;
+        }
+        Ok(())
+    }
+
+    /// Removes `path` from the history.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::History`] if history is disabled, the path is not in
+    /// the history, or the history file cannot be written.
+    pub fn forget(&mut self, path: &Path) -> Result<()> {
+        let history = self.history.as_mut().ok_or(history::Error::Disabled)?;
+        let path = std::path::absolute(path).map_err(|e| history::Error::Io(path.into(), e))?;
+        history.forget(&path)?;
+        Ok(history.save()?)
+    }
+
+    /// Returns `path` with a leading home directory abbreviated to `~`.
+    fn tilde(&self, path: &Path) -> String {
+        match path.strip_prefix(&self.home) {
+            Ok(rest) if rest.as_os_str().is_empty() => "~".to_owned(),
+            Ok(rest) => format!("~/{}", rest.display()),
+            Err(_) => path.display().to_string(),
+        }
+    }
+
+    /// Returns an error if `name` is defined in the project's `.jump.yaml`,
+    /// which even `--force` does not edit.
+    fn not_local(&self, name: &str) -> Result<()> {
+        match self.db.entry(name) {
+            Some(entry) if self.local.as_ref() == Some(&entry.location.file) => Err(Error::Local {
+                name: name.to_owned(),
+                location: entry.location.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns an error if `name` is already defined.
+    fn vacant(&self, name: &str) -> Result<()> {
+        match self.db.entry(name) {
+            Some(entry) => Err(Error::Exists {
+                name: name.to_owned(),
+                location: entry.location.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns every loaded file, in the order they were read.
+    fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.db_paths.iter().chain(&self.local)
+    }
+
+    /// Removes `name` from every `jump.yaml`, returning false if none defined
+    /// it. A project's `.jump.yaml` belongs to its repository, and is left as
+    /// it is.
+    fn remove_everywhere(&self, name: &str) -> Result<bool> {
+        let mut removed = false;
+        for path in &self.db_paths {
+            removed |= edit::remove(path, name)?;
+        }
+        Ok(removed)
+    }
+
+    /// Re-reads the database after an edit.
+    fn reload(&mut self) -> Result<()> {
+        let mut db = Database::new();
+        for path in &self.db_paths {
+            db.read_file(path)?;
+        }
+        if let Some(local) = &self.local {
+            read_local(&mut db, local);
+        }
+        self.db = db;
+        Ok(())
+    }
+
+    /// Adds target `names` for `value`, defaulting to the current directory.
+    /// Relative paths such as `.` or `../x` are made absolute, and absolute
+    /// paths are written with the home directory abbreviated to `~`. If some
+    /// loaded `jump.yaml` already has an entry for the value, the names are
+    /// added to that entry; otherwise, a new entry is appended to the last
+    /// one. Returns the path of the edited file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Exists`] if any of the names is already defined, unless
+    /// `force` is true, in which case the old definitions are removed first.
+    /// Returns [`Error::Local`] if a name is defined in the project's
+    /// `.jump.yaml`, even if `force` is true. Returns [`Error::Database`] if a
+    /// file cannot be read or written, and [`Error::NoFile`] if no `jump.yaml`
+    /// was loaded.
+    pub fn add(&mut self, names: &[String], value: Option<&str>, force: bool) -> Result<PathBuf> {
+        let value = match value {
+            Some(value) if !value.starts_with(['.', '/']) => value.to_owned(),
+            value => {
+                let value = value.unwrap_or(".");
+                let path =
+                    std::path::absolute(value).map_err(|e| db::Error::io(value.into(), e))?;
+                self.tilde(&path)
+            }
+        };
+        for name in names {
+            self.not_local(name)?;
+        }
+        for name in names {
+            if force {
+                self.remove_everywhere(name)?;
+            } else {
+                self.vacant(name)?;
+            }
+        }
+        if force {
+            self.reload()?;
+        }
+        // Prefer the file that would win the lookup, i.e., the last one.
+        let path = self
+            .db
+            .iter()
+            .filter(|(_, entry)| entry.kind == Kind::Inferred && entry.value == value)
+            .filter_map(|(_, entry)| self.db_paths.iter().position(|p| *p == entry.location.file))
+            .max()
+            .or_else(|| self.db_paths.len().checked_sub(1))
+            .ok_or(Error::NoFile)?;
+        let path = self.db_paths[path].clone();
+        edit::add(&path, names, &value)?;
+        self.reload()?;
+        Ok(path)
+    }
+
+    /// Removes the target `name` from every `jump.yaml`, along with any entry
+    /// left without names. A project's `.jump.yaml` is not edited.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Target`] if no `jump.yaml` defines the name, and
+    /// [`Error::Database`] if a file cannot be read or written.
+    pub fn remove(&mut self, name: &str) -> Result<()> {
+        if !self.remove_everywhere(name)? {
+            return Err(self.not_found(name));
+        }
+        self.reload()
+    }
+
+    /// Renames the target `old` to `new` in every `jump.yaml`. A project's
+    /// `.jump.yaml` is not edited.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Target`] if no `jump.yaml` defines `old`, and
+    /// [`Error::Exists`] if `new` is already defined, unless `force` is true,
+    /// in which case the old definitions of `new` are removed first. Returns
+    /// [`Error::Local`] if the project's `.jump.yaml` defines `new`, and
+    /// [`Error::Database`] if a file cannot be read or written.
+    pub fn rename(&mut self, old: &str, new: &str, force: bool) -> Result<()> {
+        let global = |entry: &Entry| self.db_paths.contains(&entry.location.file);
+        let defined = self.db.entry(old).is_some_and(global)
+            || self.db.shadows().iter().any(|c| c.name == old);
+        if !defined {
+            return Err(self.not_found(old));
+        }
+        if old == new {
+            return Ok(());
+        }
+        self.not_local(new)?;
+        if force {
+            self.remove_everywhere(new)?;
+        } else {
+            self.vacant(new)?;
+        }
+        for path in &self.db_paths {
+            edit::rename(path, old, new)?;
+        }
+        self.reload()
+    }
+
+    fn list_prefixed(&self, prefix: &str) -> Vec<(&str, &Entry, Result<Target>)> {
+        let expand = Expand::with_home(&self.home).with_date(self.today);
+        let mut names = self
+            .db
+            .prefixed(prefix)
+            .map(|(name, entry)| {
+                let target = self.typed(&expand, name, entry, &entry.value, &mut Vec::new());
+                (name, entry, target)
+            })
+            .collect::<Vec<_>>();
+        names.sort_unstable_by_key(|&(name, _, _)| name);
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(yaml: &str) -> App {
+        let (db, path) = db::from_yaml(yaml);
+        App {
+            home: PathBuf::from("/home/user"),
+            db,
+            db_paths: vec![path],
+            local: None,
+            cwd: None,
+            today: NaiveDate::from_ymd_opt(2025, 3, 27).unwrap(),
+            fuzzy: false,
+            mkdir: false,
+            must_exist: false,
+            history: None,
+        }
+    }
+
+    fn resolved(app: &App, target: &str) -> String {
+        match app.resolve(target, &[]).unwrap() {
+            Target::Path(path) => path.display().to_string(),
+            Target::Url(s) | Target::String(s) => s,
+            Target::Command(argv) => argv.join(" "),
+        }
+    }
+
+    #[test]
+    fn unambiguous_prefix() {
+        let app = app("~/conf: [c, conf]\n~/file: [f, file]\n");
+        assert_eq!(resolved(&app, "co"), "/home/user/conf");
+        assert_eq!(resolved(&app, "fi"), "/home/user/file");
+        assert_eq!(resolved(&app, "con/"), "/home/user/conf");
+    }
+
+    #[test]
+    fn exact_match_beats_prefix() {
+        let app = app("~/conf: [c, conf]\n~/cache: cache\n");
+        assert_eq!(resolved(&app, "c"), "/home/user/conf");
+    }
+
+    #[test]
+    fn ambiguous_prefix() {
+        let app = app("~/conf: conf\n~/cache: cache\n");
+        let Err(Error::Ambiguous { name, candidates }) = app.resolve("c", &[]) else {
+            panic!("expected ambiguity");
+        };
+        assert_eq!(name, "c");
+        let names = candidates
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["cache", "conf"]);
+    }
+
+    #[test]
+    fn parameterized_target() {
+        let app = app("https://github.com/{owner}/{1}/pull/{2}: gh\n");
+        let args = ["conf", "42", "owner=jeffs"].map(String::from);
+        let Target::Url(url) = app.resolve("gh", &args).unwrap() else {
+            panic!("expected a URL");
+        };
+        assert_eq!(url, "https://github.com/jeffs/conf/pull/42");
+    }
+
+    #[test]
+    fn queries() {
+        let app = app(concat!(
+            "https://docs.rs/releases/search?query=%s: docs\n",
+            "crates: { url: \"https://crates.io/search?q=%s\" }\n",
+        ));
+        let args = ["Vec::retain".to_owned()];
+        let Target::Url(url) = app.resolve("docs", &args).unwrap() else {
+            panic!("expected a URL");
+        };
+        assert_eq!(url, "https://docs.rs/releases/search?query=Vec%3A%3Aretain");
+        let args = ["serde", "yesterday"].map(String::from);
+        assert!(matches!(
+            app.resolve("crates", &args).unwrap(),
+            Target::Url(url) if url == "https://crates.io/search?q=serde%20yesterday"
+        ));
+    }
+
+    #[test]
+    fn parameterized_arity() {
+        let app = app("https://github.com/jeffs/{1}: gh\n");
+        assert!(matches!(app.resolve("gh", &[]), Err(Error::Template(_))));
+    }
+
+    #[test]
+    fn relative_date() {
+        let app = app("~/log/%Y/%m/%d: log\n");
+        assert_eq!(resolved(&app, "log"), "/home/user/log/2025/03/27");
+        let args = ["-1".to_owned()];
+        let Target::Path(path) = app.resolve("log", &args).unwrap() else {
+            panic!("expected a path");
+        };
+        assert_eq!(path, Path::new("/home/user/log/2025/03/26"));
+    }
+
+    #[test]
+    fn relative_date_only_for_paths() {
+        let app = app(concat!(
+            "https://example.com/a%20b/{1}: web\n",
+            "pct: { snippet: \"100% {1}\" }\n",
+        ));
+        let args = ["-1".to_owned()];
+        assert!(matches!(
+            app.resolve("web", &args).unwrap(),
+            Target::Url(url) if url == "https://example.com/a%20b/-1"
+        ));
+        assert!(matches!(
+            app.resolve("pct", &args).unwrap(),
+            Target::String(s) if s == "100% -1"
+        ));
+    }
+
+    #[test]
+    fn relative_date_with_placeholder() {
+        let app = app("~/{1}/%Y-%m-%d.md: notes\n");
+        let args = ["last-monday", "work"].map(String::from);
+        let Target::Path(path) = app.resolve("notes", &args).unwrap() else {
+            panic!("expected a path");
+        };
+        assert_eq!(path, Path::new("/home/user/work/2025-03-24.md"));
+    }
+
+    #[test]
+    fn typed_targets() {
+        let app = app(concat!(
+            "notes: { path: ./notes/%Y, keys: nt }\n",
+            "home: { path: \"~\" }\n",
+            "vault: { url: \"obsidian://open?vault=work\" }\n",
+            "status:\n  cmd: [git, status]\n  keys: [st]\n",
+            "sig: { snippet: https://example.com }\n",
+        ));
+        let dir = app.db_paths[0].parent().unwrap();
+        assert_eq!(
+            resolved(&app, "nt"),
+            dir.join("notes/2025").display().to_string()
+        );
+        assert_eq!(resolved(&app, "home"), "/home/user");
+        let Target::Url(url) = app.resolve("vault", &[]).unwrap() else {
+            panic!("expected a URL");
+        };
+        assert_eq!(url, "obsidian://open?vault=work");
+        let short = ["--short".to_owned()];
+        let Target::Command(argv) = app.resolve("st", &short).unwrap() else {
+            panic!("expected a command");
+        };
+        assert_eq!(argv, ["git", "status", "--short"]);
+        assert!(matches!(
+            app.resolve("sig", &[]).unwrap(),
+            Target::String(s) if s == "https://example.com"
+        ));
+    }
+
+    #[test]
+    fn references() {
+        let app = app(concat!(
+            "~/conf: [c, conf]\n",
+            "\"@conf/prj/rebase\": rebase\n",
+            "\"@rebase/%Y\": dated\n",
+            "https://github.com/jeffs/: gh\n",
+            "\"@gh/{1}\": repo\n",
+            "sig: { snippet: s }\n",
+            "\"@sig/x\": bad\n",
+            "\"@nonesuch\": dangling\n",
+        ));
+        assert_eq!(resolved(&app, "rebase"), "/home/user/conf/prj/rebase");
+        assert_eq!(resolved(&app, "dated"), "/home/user/conf/prj/rebase/2025");
+        let Target::Url(url) = app.resolve("repo", &["conf".to_owned()]).unwrap() else {
+            panic!("expected a URL");
+        };
+        assert_eq!(url, "https://github.com/jeffs/conf");
+        assert!(matches!(
+            app.resolve("bad", &[]),
+            Err(Error::Reference { name, referenced }) if name == "bad" && referenced == "sig"
+        ));
+        assert!(matches!(
+            app.resolve("dangling", &[]),
+            Err(Error::Target { name, .. }) if name == "nonesuch"
+        ));
+    }
+
+    #[test]
+    fn reference_cycles() {
+        let app = app("\"@b/x\": a\n\"@c\": b\n\"@a/y\": c\n\"@self\": self\n");
+        let err = app.resolve("a", &[]).unwrap_err();
+        assert!(matches!(&err, Error::Cycle(names) if names == &["a", "b", "c", "a"]));
+        assert_eq!(err.to_string(), "reference cycle: a -> b -> c -> a");
+        assert!(matches!(
+            app.resolve("self", &[]),
+            Err(Error::Cycle(names)) if names == ["self", "self"]
+        ));
+        assert!(app.list().iter().all(|(_, _, target)| target.is_err()));
+    }
+
+    #[test]
+    fn builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".jj")).unwrap();
+        std::fs::create_dir_all(root.join("prj/tool/src")).unwrap();
+        std::fs::write(root.join("prj/tool/Cargo.toml"), "").unwrap();
+        let mut app = app("\"@crate/src\": src\n\"@root/prj\": [prj, root]\n");
+        app.cwd = Some(root.join("prj/tool/src"));
+
+        let (name, target) = app.resolve_named("@root", &[]).unwrap();
+        assert_eq!(name, None);
+        assert!(matches!(target, Target::Path(p) if p == root));
+        assert_eq!(
+            resolved(&app, "@crate/src"),
+            root.join("prj/tool/src").display().to_string()
+        );
+        assert_eq!(resolved(&app, "@main"), root.display().to_string());
+        assert_eq!(
+            resolved(&app, "src"),
+            root.join("prj/tool/src").display().to_string()
+        );
+        // Built-ins take precedence over targets of the same name.
+        assert_eq!(
+            resolved(&app, "@prj"),
+            root.join("prj").display().to_string()
+        );
+        assert!(matches!(
+            app.resolve("@root", &["x".to_owned()]),
+            Err(Error::Template(_))
+        ));
+        let completions = app.complete("@");
+        let names = completions
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["@root", "@crate", "@main"]);
+
+        app.cwd = Some(root.parent().unwrap().to_owned());
+        assert!(matches!(
+            app.resolve("src", &[]),
+            Err(Error::Builtin(Builtin::Crate))
+        ));
+        assert!(app.complete("@r").is_empty());
+    }
+
+    #[test]
+    fn missing_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let app = app(&format!(
+            "{0}/a/%Y: a\nb: {{ path: {0}/b/%Y, mkdir: true }}\n",
+            root.display()
+        ));
+        assert_eq!(
+            resolved(&app, "a"),
+            root.join("a/2025").display().to_string()
+        );
+        assert!(!root.join("a").exists());
+        resolved(&app, "b");
+        assert!(root.join("b/2025").is_dir());
+
+        let app = app.with_must_exist(true);
+        assert!(matches!(
+            app.resolve("a", &[]),
+            Err(Error::Nonexistent(path)) if path == root.join("a/2025")
+        ));
+        resolved(&app, "b");
+        let app = app.with_mkdir(true);
+        resolved(&app, "a");
+        assert!(root.join("a/2025").is_dir());
+    }
+
+    #[test]
+    fn completions() {
+        let app = app("~/conf: [c, conf]\n~/cache: cache\n~/file: file\n$NONESUCH_VAR: co\n");
+        let completions = app.complete("c");
+        let names = completions
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["c", "cache", "co", "conf"]);
+        assert!(
+            matches!(&completions[0].1, Ok(Target::Path(p)) if p == Path::new("/home/user/conf"))
+        );
+        assert!(completions[2].1.is_err());
+    }
+
+    #[test]
+    fn subpaths() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("prj/jump/src")).unwrap();
+        let app = app(&format!(
+            "{}: [c, conf]\nhttps://example.com: web\n",
+            root.display()
+        ));
+        let jump = root.join("prj/jump").display().to_string();
+        assert_eq!(resolved(&app, "conf/prj/jump"), jump);
+        assert_eq!(resolved(&app, "co/prj/jump/"), jump);
+
+        let Err(Error::Subpath {
+            prefix,
+            resolved,
+            existing,
+            missing,
+            ..
+        }) = app.resolve("c/prj/jmp/src", &[])
+        else {
+            panic!("expected a missing subpath");
+        };
+        assert_eq!(prefix, "c");
+        assert_eq!(resolved, root);
+        assert_eq!(existing, root.join("prj"));
+        assert_eq!(missing, "jmp");
+
+        assert!(matches!(
+            app.resolve("web/x", &[]),
+            Err(Error::Target { .. })
+        ));
+        assert!(matches!(
+            app.resolve("x/prj", &[]),
+            Err(Error::Target { .. })
+        ));
+    }
+
+    #[test]
+    fn subpath_completions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for subdir in ["prj/jump", "prj/jj-sync", "prj/.git", "prj/other"] {
+            std::fs::create_dir_all(root.join(subdir)).unwrap();
+        }
+        std::fs::write(root.join("prj/justfile"), "").unwrap();
+        let app = app(&format!("{}: c\n", root.display()));
+        let names = |partial| {
+            app.complete(partial)
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("c/prj/j"), ["c/prj/jj-sync/", "c/prj/jump/"]);
+        assert_eq!(names("c/prj/."), ["c/prj/.git/"]);
+        assert_eq!(names("c/"), ["c/prj/"]);
+        assert!(names("x/").is_empty());
+    }
+
+    #[test]
+    fn which() {
+        let app =
+            app("~/conf: [c, conf]\n~/conf/prj: prj\n\"~\": home\nhttps://example.com: web\n");
+        let which = |path: &str| {
+            let (name, suffix) = app.which(Some(Path::new(path))).unwrap();
+            (name.to_owned(), suffix.display().to_string())
+        };
+        assert_eq!(
+            which("/home/user/conf/prj/jump/src"),
+            ("prj".into(), "jump/src".into())
+        );
+        assert_eq!(which("/home/user/conf/prj"), ("prj".into(), String::new()));
+        assert_eq!(which("/home/user/conf/etc"), ("c".into(), "etc".into()));
+        assert_eq!(
+            which("/home/user/confusing"),
+            ("home".into(), "confusing".into())
+        );
+        assert!(matches!(
+            app.which(Some(Path::new("/tmp"))),
+            Err(Error::Unnamed(_))
+        ));
+    }
+
+    #[test]
+    fn suggestions() {
+        let app = app("~/vault: [v, vault]\n~/conf: conf\n");
+        let Err(Error::Target { suggestions, .. }) = app.resolve("vualt", &[]) else {
+            panic!("expected an unknown target");
+        };
+        assert_eq!(suggestions, [("vault".to_owned(), "~/vault".to_owned())]);
+    }
+
+    #[test]
+    fn fuzzy() {
+        let app = app("~/vault: [v, vault]\n~/conf: conf\n").with_fuzzy(true);
+        assert_eq!(resolved(&app, "vualt"), "/home/user/vault");
+        assert_eq!(resolved(&app, "vualt/"), "/home/user/vault");
+        assert!(app.resolve("xyzzy", &[]).is_err());
+    }
+
+    #[test]
+    fn list() {
+        let app = app("~/conf: [c, conf]\n/tmp: tmp\n");
+        let list = app.list();
+        let names = list.iter().map(|&(name, _, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, ["c", "conf", "tmp"]);
+        assert_eq!(list[0].1.value, "~/conf");
+        assert_eq!(list[0].1.location.file, app.db_paths[0]);
+        assert!(matches!(&list[2].2, Ok(Target::Path(p)) if p == Path::new("/tmp")));
+    }
+
+    #[test]
+    fn history_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("jump/src");
+        std::fs::create_dir_all(&src).unwrap();
+        let mut app = app("~/src: s\n");
+        app.history = Some(History::load(dir.path().join("history"), 0).unwrap());
+        assert!(matches!(app.resolve("src", &[]), Err(Error::Target { .. })));
+
+        app.record(&Target::Path(src.clone())).unwrap();
+        assert_eq!(resolved(&app, "s"), "/home/user/src");
+        assert_eq!(resolved(&app, "src"), src.display().to_string());
+        assert_eq!(resolved(&app, "src/"), src.display().to_string());
+        assert!(matches!(app.resolve("", &[]), Err(Error::Target { .. })));
+        assert!(matches!(
+            app.resolve("jump", &[]),
+            Err(Error::Target { .. })
+        ));
+        let args = ["src".to_owned()];
+        let Target::Path(path) = app.resolve("jump", &args).unwrap() else {
+            panic!("expected a path");
+        };
+        assert_eq!(path, src);
+
+        app.forget(&src).unwrap();
+        assert!(matches!(app.resolve("src", &[]), Err(Error::Target { .. })));
+    }
+
+    #[test]
+    fn braced_variables() {
+        let app = app("${HOME}/x: home\n${NONESUCH_JUMP_VAR:-/d}/x: default\n");
+        let home = env::var("HOME").unwrap();
+        assert_eq!(resolved(&app, "home"), format!("{home}/x"));
+        assert_eq!(resolved(&app, "default"), "/d/x");
+    }
+
+    #[test]
+    fn local_file_discovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        std::fs::create_dir_all(root.join("src/bin")).unwrap();
+        std::fs::write(root.join(LOCAL_FILE), "").unwrap();
+        assert_eq!(
+            local_file(&root.join("src/bin")),
+            Some(root.join(LOCAL_FILE))
+        );
+        assert_eq!(local_file(&root), Some(root.join(LOCAL_FILE)));
+        assert_eq!(local_file(dir.path()), None);
+    }
+
+    #[test]
+    fn local_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/docs: [docs, d]\n~/conf: c\n"]);
+        let local = dir.path().join(LOCAL_FILE);
+        std::fs::write(
+            &local,
+            "docs: docs\napi/v2: api\nhttps://example.com: web\n",
+        )
+        .unwrap();
+        app.local = Some(local);
+        app.reload().unwrap();
+
+        let (name, target) = app.resolve_named("do", &[]).unwrap();
+        assert_eq!(name, Some("docs"));
+        assert!(matches!(target, Target::Path(p) if p == dir.path().join("docs")));
+        assert_eq!(
+            resolved(&app, "api"),
+            dir.path().join("api/v2").display().to_string()
+        );
+        assert_eq!(resolved(&app, "d"), "/home/user/docs");
+        assert_eq!(resolved(&app, "web"), "https://example.com");
+
+        let conflict = app.shadowed("docs").unwrap();
+        assert_eq!(conflict.earlier.value, "~/docs");
+        assert!(app.shadowed("api").is_none());
+        assert!(app.shadowed("c").is_none());
+    }
+
+    #[test]
+    fn local_file_is_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/docs: [docs, d]\n"]);
+        let local = dir.path().join(LOCAL_FILE);
+        let yaml = "docs: docs\napi: api\n";
+        std::fs::write(&local, yaml).unwrap();
+        app.local = Some(local.clone());
+        app.reload().unwrap();
+
+        assert!(matches!(app.remove("api"), Err(Error::Target { .. })));
+        for force in [false, true] {
+            let add = app.add(&["api".into()], Some("~/api"), force);
+            assert!(matches!(add, Err(Error::Local { name, .. }) if name == "api"));
+            let rename = app.rename("d", "api", force);
+            assert!(matches!(rename, Err(Error::Local { .. })));
+        }
+        app.rename("docs", "documents", false).unwrap();
+        assert_eq!(contents(&app, 0), "~/docs: [documents, d]\n");
+        app.remove("d").unwrap();
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), yaml);
+
+        // A file that no longer parses is skipped, rather than failing.
+        std::fs::write(&local, "docs: [\n").unwrap();
+        app.reload().unwrap();
+        assert_eq!(resolved(&app, "documents"), "/home/user/docs");
+        assert!(matches!(app.resolve("api", &[]), Err(Error::Target { .. })));
+    }
+
+    #[test]
+    fn unknown_target() {
+        let app = app("~/conf: conf\n");
+        assert!(matches!(app.resolve("x", &[]), Err(Error::Target { .. })));
+    }
+
+    /// Returns an app that reads from `yamls` in a temporary directory, which
+    /// must outlive the app.
+    fn editable(dir: &tempfile::TempDir, yamls: &[&str]) -> App {
+        let mut db = Database::new();
+        let mut db_paths = Vec::new();
+        for (index, yaml) in yamls.iter().enumerate() {
+            let path = dir.path().join(format!("{index}.yaml"));
+            std::fs::write(&path, yaml).unwrap();
+            db.read_file(&path).unwrap();
+            db_paths.push(path);
+        }
+        App {
+            home: PathBuf::from("/home/user"),
+            db,
+            db_paths,
+            local: None,
+            cwd: None,
+            today: NaiveDate::from_ymd_opt(2025, 3, 27).unwrap(),
+            fuzzy: false,
+            mkdir: false,
+            must_exist: false,
+            history: None,
+        }
+    }
+
+    fn contents(app: &App, index: usize) -> String {
+        std::fs::read_to_string(&app.db_paths[index]).unwrap()
+    }
+
+    #[test]
+    fn add_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/conf: c\n", "~/file: f\n"]);
+        let path = app.add(&["conf".into()], Some("~/conf"), false).unwrap();
+        assert_eq!(path, app.db_paths[0]);
+        assert_eq!(contents(&app, 0), "~/conf: [c, conf]\n");
+        app.add(&["x".into()], Some("/home/user/x"), false).unwrap();
+        assert_eq!(contents(&app, 1), "~/file: f\n~/x: x\n");
+        assert_eq!(resolved(&app, "x"), "/home/user/x");
+    }
+
+    #[test]
+    fn add_existing_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/conf: c\n", "~/file: f\n"]);
+        let err = app.add(&["c".into()], Some("~/x"), false).unwrap_err();
+        assert!(matches!(err, Error::Exists { name, .. } if name == "c"));
+        app.add(&["c".into()], Some("~/file"), true).unwrap();
+        assert_eq!(contents(&app, 0), "");
+        assert_eq!(contents(&app, 1), "~/file: [f, c]\n");
+    }
+
+    #[test]
+    fn add_without_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &[]);
+        let err = app.add(&["x".into()], Some("~/x"), false).unwrap_err();
+        assert!(matches!(err, Error::NoFile));
+    }
+
+    #[test]
+    fn tilde() {
+        let app = app("");
+        assert_eq!(app.tilde(Path::new("/home/user")), "~");
+        assert_eq!(app.tilde(Path::new("/home/user/conf")), "~/conf");
+        assert_eq!(app.tilde(Path::new("/home/username")), "/home/username");
+    }
+
+    #[test]
+    fn remove_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/conf: [c, conf]\n", "~/cache: c\n"]);
+        app.remove("c").unwrap();
+        assert_eq!(contents(&app, 0), "~/conf: [conf]\n");
+        assert_eq!(contents(&app, 1), "");
+        assert!(matches!(app.remove("c"), Err(Error::Target { .. })));
+    }
+
+    #[test]
+    fn rename_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = editable(&dir, &["~/conf: [c, conf]\n", "~/file: f\n"]);
+        let err = app.rename("c", "f", false).unwrap_err();
+        assert!(matches!(err, Error::Exists { name, .. } if name == "f"));
+        app.rename("c", "co", false).unwrap();
+        assert_eq!(contents(&app, 0), "~/conf: [co, conf]\n");
+        app.rename("co", "f", true).unwrap();
+        assert_eq!(contents(&app, 0), "~/conf: [f, conf]\n");
+        assert_eq!(contents(&app, 1), "");
+        assert_eq!(resolved(&app, "f"), "/home/user/conf");
+        assert!(matches!(
+            app.rename("x", "y", false),
+            Err(Error::Target { .. })
+        ));
     }
 }
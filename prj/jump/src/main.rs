@@ -7,30 +7,174 @@
 //! Each `jump.yaml` maps target values (paths, URLs) to short names or lists,
 //! like `~/conf: conf` or `~/conf: [c, conf]`.
 //!
-//! # TODO
+//! Values may contain placeholders, filled from any arguments after the
+//! target name: `{1}`, `{2}`, etc. are positional, and `{name}` is filled by an
+//! argument `name=value`. For example, given `https://github.com/{owner}/{1}`,
+//! `jump gh conf owner=jeffs` prints `https://github.com/jeffs/conf`.
 //!
-//! - [ ] Tab completion/expansion; for example:
-//!   + Name: `j mo<Tab>` => `jump month`
-//!   + Path: `j log<Tab>` => `jump /Users/jeff/log/2025/03/27`
-//! - [ ] Parameters; e.g., `jump linear TIK-42` or `jump github my-repo#42`
-//! - [ ] Relative date expansion; e.g, yesterday (syntax TBD)
-//! - [ ] Recognize unambiguous prefixes; e.g., `c|co|con|conf` for `conf`
+//! A URL may contain a query placeholder, `%s`, which is filled with the
+//! remaining arguments, joined by spaces and percent-encoded. For example,
+//! given `https://docs.rs/releases/search?query=%s: docs`, `jump docs
+//! Vec::retain` prints `https://docs.rs/releases/search?query=Vec%3A%3Aretain`.
+//! Quote any arguments that contain shell metacharacters, as usual.
+//!
+//! Paths containing `%` components are formatted with today's date, unless an
+//! argument shifts it: `jump log -1`, `jump log yesterday`, `jump log
+//! last-monday`, or `jump log 2025-03-27`.
+//!
+//! A value starting with `@` refers to another target, so that targets below a
+//! common root need not repeat it: given `~/conf: conf`, the value
+//! `"@conf/prj/rebase"` expands to `~/conf/prj/rebase`. (YAML requires the
+//! quotes.) References may be chained, but not in a cycle; jump reports any
+//! cycle as the chain of names involved.
+//!
+//! Three references are built in, and depend on the current directory:
+//! `@root` is the root of the enclosing jj or git workspace, `@crate` the
+//! nearest directory holding a `Cargo.toml`, and `@main` the default
+//! workspace of the current jj repository. They may be jumped to directly, as
+//! in `jump @root` or `jump @crate/src`, or used in values, such as
+//! `"@root/docs": docs`. They take precedence over targets named `root`,
+//! `crate`, or `main`.
+//!
+//! A target name may be followed by a path below it: `jump conf/prj/jump`
+//! resolves `conf`, and joins `prj/jump` onto the result. The joined path must
+//! exist; if it doesn't, the error names the first missing component.
+//!
+//! A name may instead map to a typed target, which states what it is rather
+//! than leaving jump to infer it from the value:
+//!
+//! ```yaml
+//! notes: { path: ./notes, keys: [n] }
+//! vault: { url: "obsidian://open?vault=work" }
+//! status: { cmd: [git, status, --short], keys: st }
+//! sig: { snippet: "Jeff Schwab" }
+//! ```
+//!
+//! A relative `path` is relative to the directory of the `jump.yaml` that
+//! defines it. Any arguments after a `cmd` target's name are appended to the
+//! command. `keys` lists further names for the target.
+//!
+//! A path target with `mkdir: true`, like `log: { path: ~/vault/log/%Y/%m/%d,
+//! mkdir: true }`, is created as a directory (along with any missing parents)
+//! if it does not exist when jump resolves it. `--mkdir` does the same for any
+//! path target. Otherwise, jump prints the path whether or not it exists,
+//! unless `--must-exist` is specified, in which case a missing path is an
+//! error.
+//!
+//! When a target is not found, the error lists similar names. With `--fuzzy`,
+//! jump resolves the most similar name instead, if it is clearly the closest.
+//!
+//! # Project targets
+//!
+//! jump also reads the nearest `.jump.yaml` in the current directory or its
+//! ancestors, the way jj finds `.jj`, so that a repository can ship targets
+//! like `docs`, `api`, and `tests` that work for everyone who clones it. Its
+//! names apply only within that tree, and take precedence over those in
+//! `$JUMP_DIRS`; when one shadows a global name, jump notes it on stderr.
+//! Its values are paths relative to its directory (`docs: docs`, or
+//! `src/api: api`), unless they are absolute, start with `~` or `$`, or are
+//! URLs. Since anyone can write one, it may define only paths and URLs: its
+//! commands and snippets are skipped. A `.jump.yaml` that cannot be parsed is
+//! skipped too, with a warning, and `jump rm` and `jump rename` never edit
+//! one.
+//!
+//! # History
+//!
+//! Every path jump resolves is recorded in `$XDG_STATE_HOME/jump/history`
+//! (defaulting to `~/.local/state/jump/history`). When a name is not in any
+//! `jump.yaml`, jump falls back to the recorded directories, zoxide-style: the
+//! name and any further arguments are keywords that must appear in the path
+//! in order, with the last in the final component, and the most frecent match
+//! (frequently and recently resolved) wins. Curated names always take
+//! precedence. `jump --forget PATH` removes a path from the history, and
+//! setting `$JUMP_NO_HISTORY` to a non-empty value disables history entirely.
+//!
+//! # Shell integration
+//!
+//! `jump --init SHELL` prints a wrapper function `f`, which follows the target
+//! (visiting directories, opening URLs in a browser, running commands, and
+//! printing snippets), along with tab completion for target names. Completing
+//! a name that matches a path exactly replaces it with the expanded path;
+//! e.g., `f log<Tab>` becomes `f /Users/jeff/vault/log/2025/03/27`. For
+//! example, in `.zshrc`:
+//!
+//! ```zsh
+//! eval "$(jump --init zsh)"
+//! ```
+//!
+//! The wrapper calls `jump --typed TARGET [ARG...]`, which prints the kind of
+//! target (`path`, `url`, `cmd`, or `snippet`) on the first line, followed by
+//! its value; for a command, each argument is on a line of its own.
+//!
+//! The completion functions call `jump --complete PARTIAL`, which prints each
+//! target name starting with `PARTIAL`, a tab, and its expanded value. If
+//! `PARTIAL` contains a slash, it instead prints the matching subdirectories
+//! below the target, like `conf/prj/`, so that `f conf/p<Tab>` completes to
+//! `f conf/prj/`.
+//!
+//! # Listing targets
+//!
+//! `jump --list [FORMAT]` prints every target in the merged database: its
+//! name, raw value, expanded value, and the file that defined it. `FORMAT` is
+//! `plain` (tab-separated, the default), `table`, or `json`. JSON listings also
+//! include each target's kind, as printed by `--typed`.
+//!
+//! `jump --which [PATH]` prints the name of the target whose path is the
+//! nearest ancestor of `PATH` (by default, the current directory), a colon,
+//! and the rest of the path; e.g., `prj:jump/src` in `~/conf/prj/jump/src`, or
+//! just `prj` in `~/conf/prj`. It's meant for prompts and terminal titles.
+//!
+//! `jump --check` reports every name defined more than once, whether twice in
+//! one file or in two files (where the later file shadows the earlier, unless
+//! it is a project's `.jump.yaml`, which is meant to), and any questionable
+//! entries, such as empty values, names that YAML doesn't read as strings, or
+//! paths with invalid `%` formats, which are skipped. Each is reported as
+//! `file:line:column: message`. It exits non-zero if it found anything; e.g.,
+//! in a pre-commit hook.
+//!
+//! # Editing targets
+//!
+//! `jump add KEY... [VALUE]` adds names for `VALUE`, which defaults to the
+//! current directory (with the home directory written as `~`). The last
+//! argument is taken as the value if it looks like one: if it contains `/`,
+//! or starts with `~`, `$`, `%`, or `@`. If some `jump.yaml` already has an entry
+//! for the value, the names are added to it; otherwise, a new entry is
+//! appended to the `jump.yaml` in the last directory of `$JUMP_DIRS`.
+//!
+//! `jump rm KEY` removes a name from every `jump.yaml`, along with any entry
+//! left without names, and `jump rename OLD NEW` renames one. Edits preserve
+//! comments, ordering, and quoting. Adding or renaming to a name that is
+//! already defined fails, unless `--force` is specified.
+//!
+//! To jump to a target named `add`, `rm`, or `rename`, write `jump -- add`.
 
+use std::borrow::Cow;
 use std::io::Write;
 use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
 use std::process::ExitCode;
 use std::{env, fmt, io};
 
+use serde::Serialize;
+
 enum ArgError {
-    /// Too many arguments were specified.
-    Extra(String),
     /// An unrecognized flag was specified.
     Flag(String),
+    /// A flag was missing its value.
+    Missing(&'static str),
+    /// An unsupported shell was passed to `--init`.
+    Shell(String),
+    /// An unsupported format was passed to `--list`.
+    Format(String),
+    /// A subcommand got the wrong number of arguments.
+    Usage(&'static str),
 }
 
 enum Error {
     /// Command-line arguments were incorrect.
     Args(ArgError),
+    /// `--check` found the specified number of problems.
+    Check(usize),
     /// The jump operation failed.
     Jump(jump::Error),
 }
@@ -50,8 +194,14 @@ impl From<jump::Error> for Error {
 impl fmt::Display for ArgError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Extra(s) => write!(f, "{s} is an unexpected argument"),
             Self::Flag(s) => write!(f, "{s} is not a recognized flag"),
+            Self::Missing(flag) => write!(f, "{flag} requires a value"),
+            Self::Shell(s) => write!(f, "{s} is not a supported shell (try {SHELLS})"),
+            Self::Format(s) => write!(
+                f,
+                "{s} is not a supported format (try plain, table, or json)"
+            ),
+            Self::Usage(command) => write!(f, "wrong number of arguments to {command}"),
         }
     }
 }
@@ -60,40 +210,454 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::Args(e) => e.fmt(f),
+            Error::Check(1) => write!(f, "found 1 problem"),
+            Error::Check(n) => write!(f, "found {n} problems"),
             Error::Jump(e) => e.fmt(f),
         }
     }
 }
 
-struct Args {
-    target: Option<String>,
+const SHELLS: &str = "zsh, bash, nu, or xonsh";
+
+/// Returns the `--init` script for the named shell.
+fn init_script(shell: &str) -> Option<&'static str> {
+    match shell {
+        "zsh" => Some(include_str!("../shell/jump.zsh")),
+        "bash" => Some(include_str!("../shell/jump.bash")),
+        "nu" | "nushell" => Some(include_str!("../shell/jump.nu")),
+        "xonsh" => Some(include_str!("../shell/jump.xsh")),
+        _ => None,
+    }
+}
+
+enum Format {
+    Plain,
+    Table,
+    Json,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "plain" => Some(Self::Plain),
+            "table" => Some(Self::Table),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+enum Command {
+    /// Print the resolved target.
+    Jump {
+        target: Option<String>,
+        /// Arguments to fill placeholders in the target value.
+        params: Vec<String>,
+        /// Whether to resolve unknown names to the most similar name.
+        fuzzy: bool,
+        /// Whether to print the kind of target before its value.
+        typed: bool,
+        /// Whether to create a path target that does not exist.
+        mkdir: bool,
+        /// Whether to fail if a path target does not exist.
+        must_exist: bool,
+    },
+    /// Print target names starting with a prefix, and their values.
+    Complete(String),
+    /// Print shell integration code.
+    Init(&'static str),
+    /// Print every target in the database.
+    List(Format),
+    /// Report names defined more than once, and other problems.
+    Check,
+    /// Print the target containing a path, by default the current directory.
+    Which(Option<PathBuf>),
+    /// Remove a path from the history.
+    Forget(PathBuf),
+    /// Add names for a value, by default the current directory.
+    Add {
+        names: Vec<String>,
+        value: Option<String>,
+        force: bool,
+    },
+    /// Remove a name.
+    Remove(String),
+    /// Rename a target.
+    Rename {
+        old: String,
+        new: String,
+        force: bool,
+    },
+}
+
+/// Returns true if `arg` looks like a flag, rather than a day offset such as
+/// `-1`.
+fn is_flag(arg: &str) -> bool {
+    arg.strip_prefix('-')
+        .is_some_and(|rest| rest.is_empty() || !rest.bytes().all(|b| b.is_ascii_digit()))
 }
 
-fn parse_args() -> Result<Args, ArgError> {
+/// Returns true if `arg` looks like a target value, rather than a name.
+fn is_value(arg: &str) -> bool {
+    arg.contains('/') || arg.starts_with(['~', '$', '%', '@'])
+}
+
+/// Parses the arguments to the `add`, `rm`, or `rename` subcommand.
+fn parse_edit(
+    command: &'static str,
+    args: impl Iterator<Item = String>,
+) -> Result<Command, ArgError> {
+    let mut force = false;
+    let mut operands = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--force" if command != "rm" => force = true,
+            _ if is_flag(&arg) => return Err(ArgError::Flag(arg)),
+            _ => operands.push(arg),
+        }
+    }
+    if command == "add" {
+        let value = if operands.len() > 1 && operands.last().is_some_and(|v| is_value(v)) {
+            operands.pop()
+        } else {
+            None
+        };
+        if operands.is_empty() || operands.iter().any(|name| is_value(name)) {
+            return Err(ArgError::Usage(command));
+        }
+        return Ok(Command::Add {
+            names: operands,
+            value,
+            force,
+        });
+    }
+    match (command, operands.as_slice()) {
+        ("rm", [name]) => Ok(Command::Remove(name.clone())),
+        ("rename", [old, new]) => Ok(Command::Rename {
+            old: old.clone(),
+            new: new.clone(),
+            force,
+        }),
+        _ => Err(ArgError::Usage(command)),
+    }
+}
+
+fn parse_args() -> Result<Command, ArgError> {
+    let mut args = env::args().skip(1);
     let mut target = None;
-    for arg in env::args().skip(1) {
-        if arg.starts_with('-') {
-            return Err(ArgError::Flag(arg));
+    let mut params = Vec::new();
+    let mut fuzzy = false;
+    let mut typed = false;
+    let mut mkdir = false;
+    let mut must_exist = false;
+    let mut operands_only = false;
+    while let Some(arg) = args.next() {
+        if operands_only {
+            if target.is_none() {
+                target = Some(arg);
+            } else {
+                params.push(arg);
+            }
+            continue;
         }
-        if target.is_some() {
-            return Err(ArgError::Extra(arg));
+        match arg.as_str() {
+            "add" if target.is_none() => return parse_edit("add", args),
+            "rm" if target.is_none() => return parse_edit("rm", args),
+            "rename" if target.is_none() => return parse_edit("rename", args),
+            "--" => operands_only = true,
+            "--complete" => {
+                let partial = args.next().ok_or(ArgError::Missing("--complete"))?;
+                return Ok(Command::Complete(partial));
+            }
+            "--init" => {
+                let shell = args.next().ok_or(ArgError::Missing("--init"))?;
+                let script = init_script(&shell).ok_or(ArgError::Shell(shell))?;
+                return Ok(Command::Init(script));
+            }
+            "--list" => {
+                let format = match args.next() {
+                    Some(format) => Format::parse(&format).ok_or(ArgError::Format(format))?,
+                    None => Format::Plain,
+                };
+                return Ok(Command::List(format));
+            }
+            "--check" => return Ok(Command::Check),
+            "--which" => return Ok(Command::Which(args.next().map(PathBuf::from))),
+            "--forget" => {
+                let path = args.next().ok_or(ArgError::Missing("--forget"))?;
+                return Ok(Command::Forget(path.into()));
+            }
+            "--fuzzy" => fuzzy = true,
+            "--typed" => typed = true,
+            "--mkdir" => mkdir = true,
+            "--must-exist" => must_exist = true,
+            _ if is_flag(&arg) => return Err(ArgError::Flag(arg)),
+            _ if target.is_none() => target = Some(arg),
+            _ => params.push(arg),
         }
-        target = Some(arg);
     }
-    Ok(Args { target })
+    Ok(Command::Jump {
+        target,
+        params,
+        fuzzy,
+        typed,
+        mkdir,
+        must_exist,
+    })
 }
 
 fn write(mut w: impl Write, s: &[u8]) {
     w.write_all(s).expect("output should be writable");
 }
 
+/// Returns `arg` quoted for a POSIX shell, unless it needs no quoting.
+fn shell_quote(arg: &str) -> Cow<'_, str> {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c));
+    if plain {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+/// Returns the output for `target`; for a command, its arguments, quoted for
+/// a shell, and separated by spaces.
+fn target_bytes(target: &jump::Target) -> Cow<'_, [u8]> {
+    match target {
+        jump::Target::Path(path) => Cow::Borrowed(path.as_os_str().as_bytes()),
+        jump::Target::Url(s) | jump::Target::String(s) => Cow::Borrowed(s.as_bytes()),
+        jump::Target::Command(argv) => {
+            let argv = argv.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>();
+            Cow::Owned(argv.join(" ").into_bytes())
+        }
+    }
+}
+
+/// Returns the kind of `target`, as printed by `--typed`.
+fn target_kind(target: &jump::Target) -> &'static str {
+    match target {
+        jump::Target::Path(_) => "path",
+        jump::Target::Url(_) => "url",
+        jump::Target::Command(_) => "cmd",
+        jump::Target::String(_) => "snippet",
+    }
+}
+
+/// Returns the `--typed` output for `target`: its kind on the first line, then
+/// its value, or for a command, each argument on a line of its own.
+fn typed_bytes(target: &jump::Target) -> Vec<u8> {
+    let mut out = target_kind(target).as_bytes().to_vec();
+    out.push(b'\n');
+    match target {
+        jump::Target::Command(argv) => {
+            for arg in argv {
+                out.extend_from_slice(arg.as_bytes());
+                out.push(b'\n');
+            }
+        }
+        target => {
+            out.extend_from_slice(&target_bytes(target));
+            out.push(b'\n');
+        }
+    }
+    out
+}
+
+/// One row of `--list` output.
+#[derive(Serialize)]
+struct Listing<'a> {
+    name: &'a str,
+    value: &'a str,
+    /// The kind of target, if expansion succeeded.
+    kind: Option<&'static str>,
+    /// The expanded value, if expansion succeeded.
+    target: Option<String>,
+    /// Why expansion failed, if it did.
+    error: Option<String>,
+    file: String,
+    line: Option<usize>,
+}
+
+impl<'a> Listing<'a> {
+    fn new(name: &'a str, entry: &'a jump::Entry, target: jump::Result<jump::Target>) -> Self {
+        let (kind, target, error) = match target {
+            Ok(target) => (
+                Some(target_kind(&target)),
+                Some(String::from_utf8_lossy(&target_bytes(&target)).into_owned()),
+                None,
+            ),
+            Err(err) => (None, None, Some(err.to_string())),
+        };
+        Listing {
+            name,
+            value: &entry.value,
+            kind,
+            target,
+            error,
+            file: entry.location.file.display().to_string(),
+            line: entry.location.line,
+        }
+    }
+
+    /// Returns the expanded value, or a description of why expansion failed.
+    fn target_or_error(&self) -> String {
+        match (&self.target, &self.error) {
+            (Some(target), _) => target.clone(),
+            (None, error) => format!("error: {}", error.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{line}", self.file),
+            None => self.file.clone(),
+        }
+    }
+}
+
+/// Returns the lines of `--list` output.
+fn list(listings: &[Listing], format: &Format) -> Vec<String> {
+    match format {
+        Format::Plain => listings
+            .iter()
+            .map(|l| {
+                let (name, value, target) = (l.name, l.value, l.target_or_error());
+                format!("{name}\t{value}\t{target}\t{}", l.location())
+            })
+            .collect(),
+        Format::Table => {
+            let header = ["NAME", "VALUE", "TARGET", "FILE"].map(str::to_owned);
+            let rows = listings
+                .iter()
+                .map(|l| {
+                    let (name, value) = (l.name.to_owned(), l.value.to_owned());
+                    [name, value, l.target_or_error(), l.location()]
+                })
+                .collect::<Vec<_>>();
+            let mut widths = header.clone().map(|h| h.len());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            let [w0, w1, w2, _] = widths;
+            std::iter::once(&header)
+                .chain(&rows)
+                .map(|[name, value, target, file]| {
+                    let line = format!("{name:w0$}  {value:w1$}  {target:w2$}  {file}");
+                    line.trim_end().to_owned()
+                })
+                .collect()
+        }
+        Format::Json => {
+            let json = serde_json::to_string_pretty(listings)
+                .unwrap_or_else(|e| unreachable!("listings are serializable: {e}"));
+            vec![json]
+        }
+    }
+}
+
 fn main_imp() -> Result<(), Error> {
-    let args = parse_args()?;
-    let app = jump::App::from_env()?;
+    let command = parse_args()?;
     let stdout = io::stdout();
-    match app.resolve(&args.target.unwrap_or_default())? {
-        jump::Target::Path(path) => write(&stdout, path.as_os_str().as_bytes()),
-        jump::Target::String(s) => write(&stdout, s.as_bytes()),
+    if let Command::Init(script) = command {
+        write(&stdout, script.as_bytes());
+        return Ok(());
+    }
+    let mut app = jump::App::from_env()?;
+    match command {
+        Command::Jump {
+            target,
+            params,
+            fuzzy,
+            typed,
+            mkdir,
+            must_exist,
+        } => {
+            let mut app = app
+                .with_fuzzy(fuzzy)
+                .with_mkdir(mkdir)
+                .with_must_exist(must_exist);
+            let (name, target) = app.resolve_named(&target.unwrap_or_default(), &params)?;
+            if let Some(conflict) = name.and_then(|name| app.shadowed(name)) {
+                eprintln!("note: {conflict}");
+            }
+            if typed {
+                write(&stdout, &typed_bytes(&target));
+            } else {
+                write(&stdout, &target_bytes(&target));
+            }
+            if let Err(err) = app.record(&target) {
+                eprintln!("warning: {err}");
+            }
+        }
+        Command::Complete(partial) => {
+            let mut out = Vec::new();
+            for (name, target) in app.complete(&partial) {
+                out.extend_from_slice(name.as_bytes());
+                if let Ok(target) = target {
+                    out.push(b'\t');
+                    out.extend_from_slice(&target_bytes(&target));
+                }
+                out.push(b'\n');
+            }
+            write(&stdout, &out);
+        }
+        Command::List(format) => {
+            let listings = app
+                .list()
+                .into_iter()
+                .map(|(name, entry, target)| Listing::new(name, entry, target))
+                .collect::<Vec<_>>();
+            let mut out = Vec::new();
+            for line in list(&listings, &format) {
+                out.extend_from_slice(line.as_bytes());
+                out.push(b'\n');
+            }
+            write(&stdout, &out);
+        }
+        Command::Check => {
+            let db = app.database();
+            let findings = db
+                .problems()
+                .iter()
+                .map(ToString::to_string)
+                .chain(db.conflicts().iter().map(ToString::to_string))
+                .collect::<Vec<_>>();
+            let mut out = Vec::new();
+            for finding in &findings {
+                out.extend_from_slice(finding.as_bytes());
+                out.push(b'\n');
+            }
+            write(&stdout, &out);
+            if !findings.is_empty() {
+                return Err(Error::Check(findings.len()));
+            }
+        }
+        Command::Which(path) => {
+            let (name, suffix) = app.which(path.as_deref())?;
+            let mut out = name.as_bytes().to_vec();
+            if !suffix.as_os_str().is_empty() {
+                out.push(b':');
+                out.extend_from_slice(suffix.as_os_str().as_bytes());
+            }
+            out.push(b'\n');
+            write(&stdout, &out);
+        }
+        Command::Forget(path) => app.forget(&path)?,
+        Command::Add {
+            names,
+            value,
+            force,
+        } => {
+            app.add(&names, value.as_deref(), force)?;
+        }
+        Command::Remove(name) => app.remove(&name)?,
+        Command::Rename { old, new, force } => app.rename(&old, &new, force)?,
+        Command::Init(_) => unreachable!("handled before loading the database"),
     }
     Ok(())
 }
@@ -102,7 +666,16 @@ fn main() -> ExitCode {
     if let Err(err) = main_imp() {
         eprintln!("error: {err}");
         if matches!(err, Error::Args(_)) {
-            eprintln!("usage: jump TARGET");
+            eprintln!("usage: jump [--fuzzy] [--typed] [--mkdir|--must-exist] TARGET [ARG...]");
+            eprintln!("       jump --complete PARTIAL");
+            eprintln!("       jump --init SHELL");
+            eprintln!("       jump --list [plain|table|json]");
+            eprintln!("       jump --check");
+            eprintln!("       jump --which [PATH]");
+            eprintln!("       jump --forget PATH");
+            eprintln!("       jump add [--force] KEY... [VALUE]");
+            eprintln!("       jump rm KEY");
+            eprintln!("       jump rename [--force] OLD NEW");
         }
         return ExitCode::FAILURE;
     }
@@ -0,0 +1,140 @@
+//! Approximate matching of unknown target names, for "did you mean" hints.
+
+use crate::db::{Database, Entry};
+
+/// A target name similar to a query.
+pub struct Match<'a> {
+    pub name: &'a str,
+    pub entry: &'a Entry,
+    /// Edit distance from the query.
+    pub distance: usize,
+    /// Whether the query's characters appear in order in the name.
+    pub subsequence: bool,
+}
+
+/// Returns the optimal string alignment distance between `a` and `b`: the
+/// number of insertions, deletions, substitutions, and transpositions of
+/// adjacent characters needed to turn one into the other.
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // Rows i-2, i-1, and i of the usual dynamic programming table.
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut row = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        (prev2, prev, row) = (prev, row, prev2);
+    }
+    prev[b.len()]
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+/// Returns the names in `db` that resemble `query`, best first. A name
+/// resembles the query if it contains the query as a subsequence, or if it
+/// is within an edit distance of roughly one third of their length.
+#[must_use]
+pub fn ranked<'a>(db: &'a Database, query: &str) -> Vec<Match<'a>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut matches = db
+        .iter()
+        .filter_map(|(name, entry)| {
+            let distance = distance(query, name);
+            let subsequence = is_subsequence(query, name);
+            let len = query.chars().count().max(name.chars().count());
+            (subsequence || distance * 3 <= len).then_some(Match {
+                name,
+                entry,
+                distance,
+                subsequence,
+            })
+        })
+        .collect::<Vec<_>>();
+    matches.sort_unstable_by_key(|m| (m.distance, !m.subsequence, m.name));
+    matches
+}
+
+/// Returns the best match for `query`, if it is strictly closer than every
+/// other match.
+#[must_use]
+pub fn best<'a>(db: &'a Database, query: &str) -> Option<Match<'a>> {
+    let mut matches = ranked(db, query).into_iter();
+    let best = matches.next()?;
+    match matches.next() {
+        Some(next) if next.distance <= best.distance => None,
+        _ => Some(best),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(yaml: &str) -> Database {
+        crate::db::from_yaml(yaml).0
+    }
+
+    fn names(db: &Database, query: &str) -> Vec<String> {
+        ranked(db, query)
+            .into_iter()
+            .map(|m| m.name.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(distance("", ""), 0);
+        assert_eq!(distance("vault", "vault"), 0);
+        assert_eq!(distance("vualt", "vault"), 1);
+        assert_eq!(distance("vaut", "vault"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn subsequences() {
+        assert!(is_subsequence("vlt", "vault"));
+        assert!(is_subsequence("", "vault"));
+        assert!(!is_subsequence("tv", "vault"));
+    }
+
+    #[test]
+    fn transposition_ranks_first() {
+        let db = db("~/vault: [v, vault]\n~/var: var\n~/file: file\n");
+        assert_eq!(names(&db, "vualt"), ["vault"]);
+        assert_eq!(best(&db, "vualt").unwrap().name, "vault");
+    }
+
+    #[test]
+    fn subsequence_matches() {
+        let db = db("~/vault: vault\n~/file: file\n");
+        assert_eq!(names(&db, "vlt"), ["vault"]);
+    }
+
+    #[test]
+    fn no_clear_winner() {
+        let db = db("~/data: data\n~/date: date\n");
+        assert_eq!(names(&db, "dat"), ["data", "date"]);
+        assert!(best(&db, "dat").is_none());
+    }
+
+    #[test]
+    fn unrelated_names() {
+        let db = db("~/vault: vault\n~/file: file\n");
+        assert!(names(&db, "xyzzy").is_empty());
+        assert!(names(&db, "").is_empty());
+    }
+}
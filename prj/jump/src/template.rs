@@ -0,0 +1,340 @@
+//! Placeholder substitution for parameterized targets.
+//!
+//! A target value may contain placeholders, which are filled from the extra
+//! command-line arguments before the value is expanded:
+//! - `{1}`, `{2}`, ... - positional arguments, numbered from one
+//! - `{name}` - named arguments, passed as `name=value`
+//!
+//! Braces around anything else (e.g., `{a,b}` or `{}`), or after a `$`, as in
+//! the variable `${HOME}`, are left alone.
+//!
+//! A URL may also contain a query placeholder, `%s`, which is filled with any
+//! arguments left over, joined by spaces and percent-encoded; e.g.,
+//! `https://docs.rs/releases/search?query=%s`.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+
+#[derive(Debug)]
+pub enum Error {
+    /// The number of positional arguments did not match the template.
+    Arity {
+        template: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// A named placeholder had no matching `name=value` argument.
+    Unnamed { template: String, name: String },
+    /// A `name=value` argument matched no placeholder in the template.
+    Unused { template: String, name: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Arity {
+                template,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{template}: expected {expected} argument(s), but got {actual}"
+            ),
+            Self::Unnamed { template, name } => {
+                write!(f, "{template}: missing argument {name}=VALUE")
+            }
+            Self::Unused { template, name } => {
+                write!(f, "{template}: no placeholder {{{name}}}")
+            }
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The placeholder in a URL for free text, such as a search query.
+pub const QUERY: &str = "%s";
+
+enum Placeholder<'a> {
+    Positional(usize),
+    Named(&'a str),
+}
+
+impl<'a> Placeholder<'a> {
+    /// Parses the text between braces, if it names a placeholder.
+    fn parse(inner: &'a str) -> Option<Self> {
+        if inner.is_empty() {
+            None
+        } else if inner.bytes().all(|b| b.is_ascii_digit()) {
+            inner.parse().ok().filter(|&n| n > 0).map(Self::Positional)
+        } else if inner.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && inner.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            Some(Self::Named(inner))
+        } else {
+            None
+        }
+    }
+}
+
+enum Part<'a> {
+    Literal(&'a str),
+    Placeholder(Placeholder<'a>),
+}
+
+/// Splits `template` into literal text and placeholders.
+fn parts(template: &str) -> impl Iterator<Item = Part<'_>> {
+    let mut rest = template;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut search = 0;
+        while let Some(open) = rest[search..].find('{').map(|i| i + search) {
+            // `${VAR}` is a variable, expanded later, rather than a placeholder.
+            if rest[..open].ends_with('$') {
+                search = open + 1;
+                continue;
+            }
+            let Some(close) = rest[open..].find('}').map(|i| i + open) else {
+                break;
+            };
+            if let Some(placeholder) = Placeholder::parse(&rest[open + 1..close]) {
+                if open > 0 {
+                    let literal = &rest[..open];
+                    rest = &rest[open..];
+                    return Some(Part::Literal(literal));
+                }
+                rest = &rest[close + 1..];
+                return Some(Part::Placeholder(placeholder));
+            }
+            search = open + 1;
+        }
+        let literal = rest;
+        rest = "";
+        Some(Part::Literal(literal))
+    })
+}
+
+/// Returns the highest positional placeholder in `template`, or zero.
+fn arity(template: &str) -> usize {
+    parts(template)
+        .filter_map(|part| match part {
+            Part::Placeholder(Placeholder::Positional(n)) => Some(n),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Percent-encodes `s`, leaving only unreserved characters (letters, digits,
+/// `-`, `.`, `_`, and `~`) as they are.
+fn encode(s: &str) -> String {
+    let mut encoded = String::new();
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+            encoded.push(char::from(b));
+        } else {
+            write!(encoded, "%{b:02X}").expect("strings are writable");
+        }
+    }
+    encoded
+}
+
+/// Fills the query placeholders in the URL `template`, then any others, like
+/// [`fill`]. Arguments fill named placeholders and then positional ones; the
+/// rest, joined by spaces and percent-encoded, fill each [`QUERY`]. An
+/// argument of the form `name=value` is part of the query unless the template
+/// has a placeholder `{name}`.
+///
+/// # Errors
+///
+/// Returns [`Error::Arity`] if there are too few positional arguments, and
+/// [`Error::Unnamed`] if a named placeholder has no matching argument.
+pub fn fill_query(template: &str, args: &[String]) -> Result<String> {
+    let names = parts(template)
+        .filter_map(|part| match part {
+            Part::Placeholder(Placeholder::Named(name)) => Some(name),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let expected = arity(template);
+    let (mut placeholders, mut query) = (Vec::new(), Vec::new());
+    let mut positional = 0;
+    for arg in args {
+        if arg
+            .split_once('=')
+            .is_some_and(|(name, _)| names.contains(&name))
+        {
+            placeholders.push(arg.clone());
+        } else if positional < expected {
+            placeholders.push(arg.clone());
+            positional += 1;
+        } else {
+            query.push(arg.as_str());
+        }
+    }
+    fill(
+        &template.replace(QUERY, &encode(&query.join(" "))),
+        &placeholders,
+    )
+}
+
+/// Fills the placeholders in `template` from `args`. Arguments of the form
+/// `name=value` fill named placeholders; all others fill positional ones, in
+/// order.
+///
+/// # Errors
+///
+/// Returns [`Error::Arity`] unless the number of positional arguments matches
+/// the highest positional placeholder, [`Error::Unnamed`] if a named
+/// placeholder has no matching argument, and [`Error::Unused`] if a named
+/// argument has no matching placeholder.
+pub fn fill(template: &str, args: &[String]) -> Result<String> {
+    let mut positional = Vec::new();
+    let mut named = HashMap::new();
+    for arg in args {
+        match arg.split_once('=') {
+            Some((name, value))
+                if matches!(Placeholder::parse(name), Some(Placeholder::Named(_))) =>
+            {
+                named.insert(name, value);
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let expected = arity(template);
+    if positional.len() != expected {
+        return Err(Error::Arity {
+            template: template.to_owned(),
+            expected,
+            actual: positional.len(),
+        });
+    }
+
+    let mut used = Vec::new();
+    let mut filled = String::new();
+    for part in parts(template) {
+        match part {
+            Part::Literal(literal) => filled.push_str(literal),
+            Part::Placeholder(Placeholder::Positional(n)) => filled.push_str(positional[n - 1]),
+            Part::Placeholder(Placeholder::Named(name)) => {
+                let value = named.get(name).ok_or_else(|| Error::Unnamed {
+                    template: template.to_owned(),
+                    name: name.to_owned(),
+                })?;
+                filled.push_str(value);
+                used.push(name);
+            }
+        }
+    }
+
+    if let Some(name) = named.keys().find(|name| !used.contains(name)) {
+        return Err(Error::Unused {
+            template: template.to_owned(),
+            name: (*name).to_owned(),
+        });
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().copied().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn positional() {
+        let filled = fill(
+            "https://github.com/jeffs/{1}/pull/{2}",
+            &args(&["conf", "42"]),
+        );
+        assert_eq!(filled.unwrap(), "https://github.com/jeffs/conf/pull/42");
+    }
+
+    #[test]
+    fn named() {
+        let filled = fill(
+            "https://github.com/{owner}/{1}",
+            &args(&["conf", "owner=jeffs"]),
+        );
+        assert_eq!(filled.unwrap(), "https://github.com/jeffs/conf");
+    }
+
+    #[test]
+    fn repeated_placeholder() {
+        assert_eq!(fill("{1}/{1}", &args(&["x"])).unwrap(), "x/x");
+    }
+
+    #[test]
+    fn non_placeholders_are_literal() {
+        assert_eq!(fill("~/{}/{0}/{-}", &[]).unwrap(), "~/{}/{0}/{-}");
+        assert_eq!(fill("~/{a,b}/{1}", &args(&["x"])).unwrap(), "~/{a,b}/x");
+        assert_eq!(fill("${HOME}/{1}", &args(&["x"])).unwrap(), "${HOME}/x");
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let err = fill("https://linear.app/issue/{1}", &[]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Arity {
+                expected: 1,
+                actual: 0,
+                ..
+            }
+        ));
+        assert!(
+            err.to_string()
+                .starts_with("https://linear.app/issue/{1}: ")
+        );
+    }
+
+    #[test]
+    fn arguments_to_plain_value() {
+        let err = fill("~/conf", &args(&["x"])).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Arity {
+                expected: 0,
+                actual: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn missing_named_argument() {
+        let err = fill("{owner}/{1}", &args(&["conf"])).unwrap_err();
+        assert!(matches!(err, Error::Unnamed { name, .. } if name == "owner"));
+    }
+
+    #[test]
+    fn queries() {
+        let filled = fill_query(
+            "https://docs.rs/releases/search?query=%s",
+            &args(&["Vec::retain", "a&b", "x=1", "é"]),
+        );
+        assert_eq!(
+            filled.unwrap(),
+            "https://docs.rs/releases/search?query=Vec%3A%3Aretain%20a%26b%20x%3D1%20%C3%A9"
+        );
+        let filled = fill_query("https://{host}/{1}?q=%s", &args(&["x", "host=h", "a b"]));
+        assert_eq!(filled.unwrap(), "https://h/x?q=a%20b");
+        assert_eq!(fill_query("https://x/?q=%s", &[]).unwrap(), "https://x/?q=");
+        assert!(matches!(
+            fill_query("https://x/{1}?q=%s", &[]),
+            Err(Error::Arity { .. })
+        ));
+    }
+
+    #[test]
+    fn unused_named_argument() {
+        let err = fill("{1}", &args(&["conf", "owner=jeffs"])).unwrap_err();
+        assert!(matches!(err, Error::Unused { name, .. } if name == "owner"));
+    }
+}
@@ -76,13 +76,19 @@ fn write_sh_var(out: &mut Vec<u8>, key: &ffi::OsStr, value: &ffi::OsStr) {
     out.extend_from_slice(b"'\n");
 }
 
-/// Writes the specified environment to a POSIX shell script.
+/// Writes the specified environment to a POSIX shell script, whose header
+/// lists the config files it was generated from.
 fn write_sh<'a>(
     dest: &Path,
     env: impl IntoIterator<Item = (&'a String, &'a EnvValue)>,
     path_env: &IndexMap<String, PathEntry>,
+    sources: &[PathBuf],
 ) -> Result<(), Box<dyn Error>> {
-    let mut out = b"# This file is generated. See ~/conf/prj/mkenv.\n\n".to_vec();
+    let mut out = b"# This file is generated. See ~/conf/prj/mkenv.\n".to_vec();
+    for source in sources {
+        out.extend_from_slice(format!("# Source: {}\n", source.display()).as_bytes());
+    }
+    out.push(b'\n');
     for (key, value) in env {
         match value {
             EnvValue::String(s) => write_sh_var(&mut out, key.as_ref(), s.as_ref()),
@@ -110,6 +116,14 @@ fn main() {
     let home = std::env::home_dir().expect("home dir");
     let conf = home.join("conf");
     let platform = platform::Platform::load(&conf).expect("loading platform config");
+    for missing in &platform.missing_paths {
+        let (key, path) = (&missing.key, missing.path.display());
+        if missing.dropped {
+            eprintln!("note: dropped {key} entry {path}, which does not exist");
+        } else {
+            eprintln!("warning: {key} entry {path} does not exist");
+        }
+    }
 
     // Make sure the destination directory exists.
     let var = conf.join("var");
@@ -122,6 +136,6 @@ fn main() {
 
     // Save exports for POSIX shells.
     let sh = var.join("env.sh");
-    write_sh(&sh, &platform.env, &platform.path_env)
+    write_sh(&sh, &platform.env, &platform.path_env, &platform.sources)
         .unwrap_or_else(|e| panic!("{}: {e}", sh.display()));
 }
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use toml::Value;
+
+use crate::{Error, MissingPolicy, expand_tilde, path_item};
+
+/// Replace each `${KEY}` in the strings of `env` and `paths` with the value of
+/// `KEY` in `env`, `paths`, or `vars` (in that order), and each `${env:VAR}`
+/// with the variable `VAR` in this process's environment. `$${` stands for a
+/// literal `${`.
+///
+/// A key stands for its final value: an `[env]` or `[vars]` string with any
+/// leading `~` expanded, or a `[paths]` entry joined onto `home`, with the
+/// directories of a list separated as in `PATH`. Like the list itself, the
+/// text leaves out any directory that does not exist, if its own `if_exists`
+/// or else `policy` says to drop it.
+/// Keys are resolved in dependency order, so a value may refer to keys
+/// written after it; both maps keep their order.
+///
+/// # Errors
+///
+/// Returns [`Error::Undefined`] if a reference names no key or variable, and
+/// [`Error::ReferenceCycle`] if keys refer to each other in a cycle.
+pub fn interpolate(
+    env: &mut IndexMap<String, Value>,
+    paths: &mut IndexMap<String, Value>,
+    vars: &IndexMap<String, Value>,
+    policy: MissingPolicy,
+    home: &Path,
+) -> Result<(), Error> {
+    let mut interpolator = Interpolator {
+        env: &env.clone(),
+        paths: &paths.clone(),
+        vars,
+        policy,
+        home,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+    };
+    for (key, value) in env.iter_mut().chain(paths.iter_mut()) {
+        interpolator.stack.push(key.clone());
+        *value = interpolator.value(value)?;
+        interpolator.stack.pop();
+    }
+    Ok(())
+}
+
+struct Interpolator<'a> {
+    env: &'a IndexMap<String, Value>,
+    paths: &'a IndexMap<String, Value>,
+    vars: &'a IndexMap<String, Value>,
+    policy: MissingPolicy,
+    home: &'a Path,
+    /// The final text of each key resolved so far.
+    resolved: HashMap<String, String>,
+    /// The keys being resolved, each referred to by the one before.
+    stack: Vec<String>,
+}
+
+impl Interpolator<'_> {
+    /// `value`, with references in its strings (however nested) replaced.
+    fn value(&mut self, value: &Value) -> Result<Value, Error> {
+        Ok(match value {
+            Value::String(s) => Value::String(self.string(s)?),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.value(item))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Table(table) => Value::Table(
+                table
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), self.value(v)?)))
+                    .collect::<Result<_, Error>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    /// `s`, with references replaced, and each `$${` replaced by `${`. A
+    /// `${` without a closing brace is left as it is.
+    fn string(&mut self, s: &str) -> Result<String, Error> {
+        let mut out = String::new();
+        let mut rest = s;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            let Some(len) = rest[start + 2..].find('}') else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            let reference = &rest[start + 2..start + 2 + len];
+            out.push_str(&self.reference(reference)?);
+            rest = &rest[start + 3 + len..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// The text a reference such as `HOME_BIN` or `env:USER` stands for.
+    fn reference(&mut self, reference: &str) -> Result<String, Error> {
+        let undefined = |stack: &[String]| Error::Undefined {
+            key: stack.last().cloned().unwrap_or_default(),
+            reference: reference.to_owned(),
+        };
+        if let Some(var) = reference.strip_prefix("env:") {
+            return std::env::var(var).map_err(|_| undefined(&self.stack));
+        }
+        if let Some(text) = self.resolved.get(reference) {
+            return Ok(text.clone());
+        }
+        if self.stack.iter().any(|key| key == reference) {
+            let mut cycle = std::mem::take(&mut self.stack);
+            cycle.push(reference.to_owned());
+            return Err(Error::ReferenceCycle(cycle));
+        }
+
+        self.stack.push(reference.to_owned());
+        let text = if let Some(value) = self.env.get(reference) {
+            self.scalar(value)?
+        } else if let Some(value) = self.paths.get(reference) {
+            // As in `Platform::resolve`, the policy is for lists only.
+            let (items, policy) = match self.value(value)? {
+                Value::Array(items) => (items, self.policy),
+                single => (vec![single], MissingPolicy::Keep),
+            };
+            let dirs = items.into_iter().filter_map(|item| {
+                let (path, if_exists) = path_item(item, self.home)?;
+                (path.exists() || policy.missing(if_exists).0).then_some(path)
+            });
+            std::env::join_paths(dirs)
+                .map_err(|e| Error::Io(std::io::Error::other(e)))?
+                .to_string_lossy()
+                .into_owned()
+        } else if let Some(value) = self.vars.get(reference) {
+            self.scalar(value)?
+        } else {
+            self.stack.pop();
+            return Err(undefined(&self.stack));
+        };
+        self.stack.pop();
+
+        self.resolved.insert(reference.to_owned(), text.clone());
+        Ok(text)
+    }
+
+    /// The text an `[env]` or `[vars]` value stands for.
+    fn scalar(&mut self, value: &Value) -> Result<String, Error> {
+        Ok(match self.value(value)? {
+            Value::String(s) => expand_tilde(&s, self.home),
+            other => other.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Maps = (IndexMap<String, Value>, IndexMap<String, Value>);
+
+    /// The `[env]` and `[paths]` of `toml`, interpolated using its `[vars]`.
+    fn interpolated(toml: &str, home: &str, policy: MissingPolicy) -> Result<Maps, Error> {
+        let mut table: toml::Table = toml::from_str(toml).unwrap();
+        let mut section = |name| match table.remove(name) {
+            Some(Value::Table(t)) => t.into_iter().collect(),
+            _ => IndexMap::new(),
+        };
+        let (mut env, mut paths, vars) = (section("env"), section("paths"), section("vars"));
+        interpolate(&mut env, &mut paths, &vars, policy, Path::new(home))?;
+        Ok((env, paths))
+    }
+
+    #[test]
+    fn references_resolve_in_dependency_order() {
+        let (env, paths) = interpolated(
+            r#"
+            [env]
+            GREETING = "${NAME}, in ${CONF}"
+            NAME = "~/${WHO}"
+            WHO = "me"
+            [paths]
+            LIB = ["${PREFIX}/lib", { path = "${PREFIX}/lib64", if_exists = true }]
+            PREFIX = ".opam/default"
+            CONF = "conf"
+            "#,
+            "/home/u",
+            MissingPolicy::Keep,
+        )
+        .unwrap();
+
+        let keys: Vec<&str> = env.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["GREETING", "NAME", "WHO"]);
+        assert_eq!(
+            env["GREETING"].as_str(),
+            Some("/home/u/me, in /home/u/conf")
+        );
+        assert_eq!(env["NAME"].as_str(), Some("~/me"));
+        assert_eq!(paths["LIB"][0].as_str(), Some("/home/u/.opam/default/lib"));
+        assert_eq!(
+            paths["LIB"][1]["path"].as_str(),
+            Some("/home/u/.opam/default/lib64")
+        );
+    }
+
+    #[test]
+    fn lists_join_as_paths() {
+        let (env, _) = interpolated(
+            r#"
+            [env]
+            ALL = "${DIRS}"
+            [paths]
+            DIRS = ["a", "/b"]
+            "#,
+            "/h",
+            MissingPolicy::Keep,
+        )
+        .unwrap();
+        assert_eq!(env["ALL"].as_str(), Some("/h/a:/b"));
+    }
+
+    #[test]
+    fn vars_are_for_references_only() {
+        let (env, paths) = interpolated(
+            r#"
+            [env]
+            ETC = "${CONF}/etc"
+            [paths]
+            BIN = "${CONF}/bin"
+            [vars]
+            CONF = "~/${NAME}"
+            NAME = "conf"
+            "#,
+            "/h",
+            MissingPolicy::Keep,
+        )
+        .unwrap();
+        assert_eq!(env["ETC"].as_str(), Some("/h/conf/etc"));
+        assert_eq!(paths["BIN"].as_str(), Some("/h/conf/bin"));
+        assert!(!env.contains_key("CONF") && !paths.contains_key("CONF"));
+    }
+
+    #[test]
+    fn escapes() {
+        let toml = "[env]\nA = \"$${B} is ${B}, for $$5\"\nB = \"b\"\n";
+        let (env, _) = interpolated(toml, "/h", MissingPolicy::Keep).unwrap();
+        assert_eq!(env["A"].as_str(), Some("${B} is b, for $$5"));
+    }
+
+    #[test]
+    fn lists_leave_out_what_they_drop() {
+        let toml = r#"
+            [env]
+            ALL = "${DIRS}"
+            ONE = "${GONE}"
+            [paths]
+            DIRS = ["/", { path = "/nonesuch-platform-test", if_exists = true }, "/nonesuch-kept"]
+            GONE = "/nonesuch-platform-test"
+            "#;
+        let (env, _) = interpolated(toml, "/h", MissingPolicy::Keep).unwrap();
+        assert_eq!(env["ALL"].as_str(), Some("/:/nonesuch-kept"));
+        assert_eq!(env["ONE"].as_str(), Some("/nonesuch-platform-test"));
+        let (env, _) = interpolated(toml, "/h", MissingPolicy::Drop).unwrap();
+        assert_eq!(env["ALL"].as_str(), Some("/"));
+        assert_eq!(env["ONE"].as_str(), Some("/nonesuch-platform-test"));
+    }
+
+    #[test]
+    fn ambient_environment() {
+        let (env, _) = interpolated(
+            "[env]\nP = \"${env:PATH}!\"\nOPEN = \"${\"\n",
+            "/h",
+            MissingPolicy::Keep,
+        )
+        .unwrap();
+        let expected = format!("{}!", std::env::var("PATH").unwrap());
+        assert_eq!(env["P"].as_str(), Some(expected.as_str()));
+        assert_eq!(env["OPEN"].as_str(), Some("${"));
+    }
+
+    #[test]
+    fn undefined_references() {
+        for toml in [
+            "[env]\nA = \"${B}\"\n",
+            "[env]\nA = \"${env:PLATFORM_TEST_SURELY_UNSET}\"\n",
+        ] {
+            let err = interpolated(toml, "/h", MissingPolicy::Keep).unwrap_err();
+            assert!(
+                matches!(&err, Error::Undefined { key, .. } if key == "A"),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn cycles() {
+        let err = interpolated(
+            r#"
+            [env]
+            A = "${B}"
+            [paths]
+            B = ["${C}"]
+            C = "${A}/c"
+            "#,
+            "/h",
+            MissingPolicy::Keep,
+        )
+        .unwrap_err();
+        let Error::ReferenceCycle(keys) = err else {
+            panic!("expected a cycle, got {err}");
+        };
+        assert_eq!(keys, ["A", "B", "C", "A"]);
+    }
+}
@@ -1,12 +1,14 @@
+mod interpolate;
 mod merge;
 
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::{fmt, fs, io};
 
 use indexmap::IndexMap;
 use serde::Deserialize;
 
-use merge::deep_merge;
+use merge::{deep_merge, is_directives};
 
 // ---------------------------------------------------------------------------
 // Error
@@ -15,6 +17,21 @@ use merge::deep_merge;
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
+    /// A config file, or one it extends, could not be read.
+    File(PathBuf, io::Error),
+    /// A config file's `extends` was not an array of file names.
+    Extends(PathBuf),
+    /// Config files extend each other in a cycle. The first and last paths
+    /// are the same.
+    Cycle(Vec<PathBuf>),
+    /// A `${...}` in the value of `key` named no key or environment variable.
+    Undefined {
+        key: String,
+        reference: String,
+    },
+    /// `[env]` or `[paths]` values refer to each other in a cycle. The first
+    /// and last keys are the same.
+    ReferenceCycle(Vec<String>),
     Toml(toml::de::Error),
     UnsupportedOs,
     NoHomeDir,
@@ -24,6 +41,22 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(e) => write!(f, "{e}"),
+            Self::File(path, e) => write!(f, "{}: {e}", path.display()),
+            Self::Extends(path) => write!(
+                f,
+                "{}: `extends` should be an array of file names",
+                path.display()
+            ),
+            Self::Cycle(paths) => {
+                let paths: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "config files extend each other: {}", paths.join(" -> "))
+            }
+            Self::Undefined { key, reference } => {
+                write!(f, "{key}: `${{{reference}}}` is not defined")
+            }
+            Self::ReferenceCycle(keys) => {
+                write!(f, "values refer to each other: {}", keys.join(" -> "))
+            }
             Self::Toml(e) => write!(f, "{e}"),
             Self::UnsupportedOs => write!(f, "unsupported operating system"),
             Self::NoHomeDir => write!(f, "could not determine home directory"),
@@ -63,12 +96,28 @@ pub enum PathEntry {
     Multi(Vec<PathBuf>),
 }
 
+/// A `[paths]` entry naming something that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingPath {
+    /// The variable the entry belongs to, such as `PATH`.
+    pub key: String,
+    pub path: PathBuf,
+    /// Whether the entry was left out, rather than kept despite missing.
+    pub dropped: bool,
+}
+
 #[derive(Debug)]
 pub struct Platform {
     pub path_env: IndexMap<String, PathEntry>,
     pub package_manager: PackageManager,
     pub system_update: SystemUpdate,
     pub env: IndexMap<String, EnvValue>,
+    /// Every file that contributed, in the order merged: later files take
+    /// precedence.
+    pub sources: Vec<PathBuf>,
+    /// Entries dropped for not existing, and those kept despite it under the
+    /// `warn` policy, in order.
+    pub missing_paths: Vec<MissingPath>,
 }
 
 #[derive(Debug)]
@@ -95,6 +144,34 @@ struct RawPlatform {
     system_update: RawSystemUpdate,
     #[serde(default)]
     env: IndexMap<String, toml::Value>,
+    /// Values for `${KEY}` references only, which are not exported.
+    #[serde(default)]
+    vars: IndexMap<String, toml::Value>,
+    #[serde(default)]
+    missing_paths: MissingPolicy,
+}
+
+/// What to do with a `[paths]` list entry whose directory does not exist,
+/// unless the entry says otherwise with `if_exists`.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MissingPolicy {
+    #[default]
+    Keep,
+    Drop,
+    Warn,
+}
+
+impl MissingPolicy {
+    /// Whether to keep a list entry that does not exist, given its own
+    /// `if_exists`, and whether to report it in [`Platform::missing_paths`].
+    fn missing(self, if_exists: Option<bool>) -> (bool, bool) {
+        match (if_exists, self) {
+            (Some(true), _) | (None, MissingPolicy::Drop) => (false, true),
+            (Some(false), _) | (None, MissingPolicy::Keep) => (true, false),
+            (None, MissingPolicy::Warn) => (true, true),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -125,8 +202,32 @@ fn expand_tilde(value: &str, home: &Path) -> String {
     value.to_owned()
 }
 
+/// Read a `[paths]` item, either a string or a table such as `{ path =
+/// ".ghcup/bin", if_exists = true }`, as a path relative to `home`, along with
+/// its `if_exists`, if any. Items of any other shape are ignored, though
+/// loading warns about them; see [`item_problems`].
+fn path_item(value: toml::Value, home: &Path) -> Option<(PathBuf, Option<bool>)> {
+    match value {
+        toml::Value::String(s) => Some((home.join(s), None)),
+        toml::Value::Table(table) => {
+            let path = table.get("path")?.as_str()?;
+            let if_exists = table.get("if_exists").and_then(toml::Value::as_bool);
+            Some((home.join(path), if_exists))
+        }
+        _ => None,
+    }
+}
+
 /// Top-level keys recognized in platform and site TOML files.
-const KNOWN_KEYS: &[&str] = &["paths", "package_manager", "system_update", "env"];
+const KNOWN_KEYS: &[&str] = &[
+    "extends",
+    "missing_paths",
+    "paths",
+    "package_manager",
+    "system_update",
+    "env",
+    "vars",
+];
 
 fn unknown_keys(table: &toml::Table) -> impl Iterator<Item = &str> {
     table
@@ -145,6 +246,114 @@ fn warn_unknown_keys(table: &toml::Table, source: &Path) {
     }
 }
 
+/// Keys recognized in a `[paths]` item written as a table.
+const ITEM_KEYS: &[&str] = &["path", "if_exists"];
+
+/// Describe what is wrong with each `[paths]` item, in lists or directives,
+/// that [`path_item`] would ignore or misread, such as a misspelled key.
+fn item_problems(paths: &toml::Table) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (key, value) in paths {
+        let items: Vec<&toml::Value> = match value {
+            toml::Value::Array(items) => items.iter().collect(),
+            toml::Value::Table(table) if is_directives(table) => table
+                .values()
+                .flat_map(|value| match value {
+                    toml::Value::Array(items) => items.iter().collect(),
+                    value => vec![value],
+                })
+                .collect(),
+            value => vec![value],
+        };
+        for item in items {
+            let toml::Value::Table(table) = item else {
+                if !item.is_str() {
+                    problems.push(format!(
+                        "paths.{key}: ignoring item {item}, not a string or table"
+                    ));
+                }
+                continue;
+            };
+            for name in table.keys().filter(|k| !ITEM_KEYS.contains(&k.as_str())) {
+                problems.push(format!(
+                    "paths.{key}: unrecognized item key `{name}` (known keys: {})",
+                    ITEM_KEYS.join(", "),
+                ));
+            }
+            if !table.get("path").is_some_and(toml::Value::is_str) {
+                problems.push(format!(
+                    "paths.{key}: ignoring item {item}, whose `path` is not a string"
+                ));
+            }
+            if table.get("if_exists").is_some_and(|v| !v.is_bool()) {
+                problems.push(format!(
+                    "paths.{key}: `if_exists` is not a boolean in item {item}"
+                ));
+            }
+        }
+    }
+    problems
+}
+
+fn warn_item_problems(table: &toml::Table, source: &Path) {
+    if let Some(toml::Value::Table(paths)) = table.get("paths") {
+        for problem in item_problems(paths) {
+            eprintln!("warning: {}: {problem}", source.display());
+        }
+    }
+}
+
+/// Merge the TOML file at `path` into `table`, after the files named by its
+/// top-level `extends` array, in order.
+///
+/// Each extended file is relative to the directory of the file naming it, and
+/// may itself extend others. `chain` holds the files whose `extends` led here,
+/// so that a file extending itself, however indirectly, is caught rather than
+/// read forever. Each file read is appended to `sources`, in merge order.
+///
+/// Array directives such as `PATH = { append = [...] }` edit whatever `table`
+/// holds by then; see [`deep_merge`].
+fn read_layered(
+    path: &Path,
+    table: &mut toml::Table,
+    chain: &mut Vec<PathBuf>,
+    sources: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let canonical = fs::canonicalize(path).map_err(|e| Error::File(path.to_owned(), e))?;
+    if chain.contains(&canonical) {
+        chain.push(canonical);
+        return Err(Error::Cycle(std::mem::take(chain)));
+    }
+    let text = fs::read_to_string(path).map_err(|e| Error::File(path.to_owned(), e))?;
+    let mut layer: toml::Table = toml::from_str(&text)?;
+    warn_unknown_keys(&layer, path);
+    warn_item_problems(&layer, path);
+
+    let extends = match layer.remove("extends") {
+        None => Vec::new(),
+        Some(toml::Value::Array(names)) => names
+            .into_iter()
+            .map(|name| match name {
+                toml::Value::String(name) => Ok(name),
+                _ => Err(Error::Extends(path.to_owned())),
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(Error::Extends(path.to_owned())),
+    };
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    chain.push(canonical);
+    for name in extends {
+        read_layered(&dir.join(name), table, chain, sources)?;
+    }
+    chain.pop();
+    for unremoved in deep_merge(table, layer) {
+        eprintln!("warning: {}: {unremoved}", path.display());
+    }
+    sources.push(path.to_owned());
+    Ok(())
+}
+
 fn platform_toml_name() -> Result<&'static str, Error> {
     if cfg!(target_os = "macos") {
         Ok("macos.toml")
@@ -157,6 +366,25 @@ fn platform_toml_name() -> Result<&'static str, Error> {
     }
 }
 
+/// The short name of this host, as `hostname` reports it, without any domain.
+/// Prints a warning and returns [`None`] if there is no such name, in which
+/// case no host file is merged.
+fn hostname() -> Option<String> {
+    let problem = match Command::new("hostname").output() {
+        Ok(output) if output.status.success() => {
+            let name = String::from_utf8_lossy(&output.stdout);
+            match name.trim().split('.').next() {
+                Some(name) if !name.is_empty() => return Some(name.to_owned()),
+                _ => "printed no name".to_owned(),
+            }
+        }
+        Ok(output) => format!("failed ({})", output.status),
+        Err(e) => format!("could not run: {e}"),
+    };
+    eprintln!("warning: hostname {problem}; skipping etc/platform/hosts");
+    None
+}
+
 // ---------------------------------------------------------------------------
 // Platform impl
 // ---------------------------------------------------------------------------
@@ -166,60 +394,105 @@ impl Platform {
     ///
     /// `conf_root` is the `~/conf` directory (parent of both `etc/` and `var/`).
     ///
+    /// Files merge in this order, each taking precedence over those before:
+    ///
+    /// 1. `etc/platform/<os>.toml`, such as `macos.toml` or `linux.toml`
+    /// 2. `etc/platform/arch/<arch>.toml`, such as `aarch64.toml`, if present
+    /// 3. `etc/platform/hosts/<hostname>.toml`, if present, where the
+    ///    hostname is stripped of any domain
+    /// 4. `var/site.toml`, if present, which is untracked
+    ///
+    /// Any of them may name an array of files to build on, such as
+    /// `extends = ["common.toml"]`; see [`read_layered`]. The files that
+    /// contributed are listed in [`Platform::sources`].
+    ///
+    /// A `[paths]` item may be written `{ path = "...", if_exists = true }`,
+    /// to leave it out unless it exists (or `false`, to keep it regardless).
+    /// Other list items that do not exist are kept, dropped, or kept with a
+    /// warning, per the top-level `missing_paths = "keep" | "drop" | "warn"`,
+    /// which defaults to `"keep"`. See [`Platform::missing_paths`].
+    ///
+    /// Strings in `[env]` and `[paths]` may refer to other keys in either, or
+    /// in `[vars]`, which holds values for such references only, as `${KEY}`;
+    /// and to the ambient environment, as `${env:VAR}`. `$${` is a literal
+    /// `${`. See [`interpolate::interpolate`].
+    ///
     /// # Errors
     ///
-    /// Returns an error if the platform TOML cannot be read or parsed, or if
-    /// the home directory cannot be determined.
+    /// Returns an error if the platform TOML cannot be read or parsed, if its
+    /// values cannot be interpolated, or if the home directory cannot be
+    /// determined.
     pub fn load(conf_root: &Path) -> Result<Self, Error> {
         let home = std::env::home_dir().ok_or(Error::NoHomeDir)?;
 
         let toml_name = platform_toml_name()?;
-        let platform_path = conf_root.join("etc/platform").join(toml_name);
+        let etc = conf_root.join("etc/platform");
+        let platform_path = etc.join(toml_name);
+        let arch_path = etc
+            .join("arch")
+            .join(format!("{}.toml", std::env::consts::ARCH));
+        let host_path = hostname().map(|host| etc.join("hosts").join(format!("{host}.toml")));
         let site_path = conf_root.join("var/site.toml");
 
-        Self::load_from(&platform_path, &site_path, &home)
+        let overlays: Vec<PathBuf> = [Some(arch_path), host_path, Some(site_path)]
+            .into_iter()
+            .flatten()
+            .collect();
+        Self::load_from(&platform_path, &overlays, &home)
     }
 
-    /// Load from explicit file paths (useful for testing).
-    fn load_from(platform_path: &Path, site_path: &Path, home: &Path) -> Result<Self, Error> {
-        let platform_str = fs::read_to_string(platform_path)?;
-        let mut table: toml::Table = toml::from_str(&platform_str)?;
-        warn_unknown_keys(&table, platform_path);
+    /// Load from explicit file paths (useful for testing). Each of `overlays`
+    /// that exists is merged, in order, over the platform file.
+    fn load_from(platform_path: &Path, overlays: &[PathBuf], home: &Path) -> Result<Self, Error> {
+        let mut sources = Vec::new();
+        let mut table = toml::Table::new();
+        read_layered(platform_path, &mut table, &mut Vec::new(), &mut sources)?;
 
-        if site_path.is_file() {
-            let site_str = fs::read_to_string(site_path)?;
-            let site_table: toml::Table = toml::from_str(&site_str)?;
-            warn_unknown_keys(&site_table, site_path);
-            deep_merge(&mut table, site_table);
+        for overlay in overlays.iter().filter(|path| path.is_file()) {
+            read_layered(overlay, &mut table, &mut Vec::new(), &mut sources)?;
         }
 
-        let raw: RawPlatform = toml::Value::Table(table).try_into()?;
-        Ok(Self::resolve(raw, home))
+        let mut raw: RawPlatform = toml::Value::Table(table).try_into()?;
+        let policy = raw.missing_paths;
+        interpolate::interpolate(&mut raw.env, &mut raw.paths, &raw.vars, policy, home)?;
+        Ok(Self::resolve(raw, home, sources))
     }
 
-    fn resolve(raw: RawPlatform, home: &Path) -> Self {
-        let path_env = raw
-            .paths
-            .into_iter()
-            .filter_map(|(key, value)| match value {
+    fn resolve(raw: RawPlatform, home: &Path, sources: Vec<PathBuf>) -> Self {
+        let mut path_env = IndexMap::new();
+        let mut missing_paths = Vec::new();
+        for (key, value) in raw.paths {
+            let mut keep = |value, policy: MissingPolicy| {
+                let (path, if_exists) = path_item(value, home)?;
+                if path.exists() {
+                    return Some(path);
+                }
+                let (kept, report) = policy.missing(if_exists);
+                if report {
+                    missing_paths.push(MissingPath {
+                        key: key.clone(),
+                        path: path.clone(),
+                        dropped: !kept,
+                    });
+                }
+                kept.then_some(path)
+            };
+            let entry = match value {
                 toml::Value::Array(arr) => {
                     let dirs: Vec<PathBuf> = arr
                         .into_iter()
-                        .filter_map(|v| match v {
-                            toml::Value::String(s) => Some(home.join(&s)),
-                            _ => None,
-                        })
+                        .filter_map(|v| keep(v, raw.missing_paths))
                         .collect();
-                    if dirs.is_empty() {
-                        None
-                    } else {
-                        Some((key, PathEntry::Multi(dirs)))
-                    }
+                    (!dirs.is_empty()).then_some(PathEntry::Multi(dirs))
                 }
-                toml::Value::String(s) => Some((key, PathEntry::Single(home.join(&s)))),
-                _ => None,
-            })
-            .collect();
+                // The policy is for directories in lists; single paths are
+                // often files, and dropped only if they say so.
+                value => keep(value, MissingPolicy::Keep).map(PathEntry::Single),
+            };
+            if let Some(entry) = entry {
+                path_env.insert(key, entry);
+            }
+        }
 
         let env = raw
             .env
@@ -242,6 +515,8 @@ impl Platform {
                 command: raw.system_update.command,
             },
             env,
+            sources,
+            missing_paths,
         }
     }
 }
@@ -268,6 +543,18 @@ mod tests {
         PathBuf::from("/Users/testuser")
     }
 
+    /// A fresh directory holding `files`, given as (name, contents) pairs.
+    fn scratch(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("platform-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (file, contents) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
     #[test]
     fn load_macos_toml() {
         let root = conf_root();
@@ -275,7 +562,7 @@ mod tests {
         let site_path = root.join("var/nonexistent-site.toml");
         let home = fake_home();
 
-        let p = Platform::load_from(&platform_path, &site_path, &home).unwrap();
+        let p = Platform::load_from(&platform_path, &[site_path], &home).unwrap();
 
         assert!(p.path_env.contains_key("PATH"));
         assert!(p.path_env.contains_key("JUMP_DIRS"));
@@ -287,6 +574,241 @@ mod tests {
         assert!(!p.env.is_empty());
     }
 
+    #[test]
+    fn load_linux_toml() {
+        let root = conf_root();
+        let platform_path = root.join("etc/platform/linux.toml");
+        let site_path = root.join("var/nonexistent-site.toml");
+
+        let p = Platform::load_from(&platform_path, &[site_path], &fake_home()).unwrap();
+
+        assert!(p.path_env.contains_key("PATH"));
+        // Shared with macOS, through common.toml.
+        assert!(p.path_env.contains_key("JUMP_DIRS"));
+        assert!(p.env.contains_key("LESS"));
+        assert!(!p.env.contains_key("HOMEBREW_NO_ENV_HINTS"));
+        // Only references use `[vars]`.
+        assert!(!p.path_env.contains_key("CONF") && !p.env.contains_key("CONF"));
+        let PathEntry::Multi(dirs) = &p.path_env["JUMP_DIRS"] else {
+            panic!("JUMP_DIRS should be a list");
+        };
+        assert_eq!(dirs, &[fake_home().join("conf/etc")]);
+    }
+
+    #[test]
+    fn extends_merges_in_order() {
+        let dir = scratch(
+            "extends",
+            &[
+                ("base/a.toml", "[env]\nA = \"a\"\nB = \"a\"\nC = \"a\"\n"),
+                ("base/b.toml", "extends = [\"a.toml\"]\n[env]\nB = \"b\"\n"),
+                ("c.toml", "[env]\nC = \"c\"\n"),
+                (
+                    "os.toml",
+                    concat!(
+                        "extends = [\"base/b.toml\", \"c.toml\"]\n",
+                        "[package_manager]\nname = \"x\"\ninstall = []\nupgrade = []\n",
+                        "[system_update]\ncommand = []\n",
+                        "[env]\nD = \"os\"\n",
+                    ),
+                ),
+            ],
+        );
+
+        let p =
+            Platform::load_from(&dir.join("os.toml"), &[dir.join("none")], &fake_home()).unwrap();
+        let env: Vec<(&str, &EnvValue)> = p.env.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        let s = |v: &str| EnvValue::String(v.to_owned());
+        assert_eq!(
+            env,
+            [
+                ("A", &s("a")),
+                ("B", &s("b")),
+                ("C", &s("c")),
+                ("D", &s("os"))
+            ]
+        );
+        let sources: Vec<_> = p
+            .sources
+            .iter()
+            .map(|p| p.strip_prefix(&dir).unwrap())
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                Path::new("base/a.toml"),
+                Path::new("base/b.toml"),
+                Path::new("c.toml"),
+                Path::new("os.toml"),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn overlays_apply_in_order() {
+        let dir = scratch(
+            "overlays",
+            &[
+                (
+                    "os.toml",
+                    concat!(
+                        "[package_manager]\nname = \"x\"\ninstall = []\nupgrade = []\n",
+                        "[system_update]\ncommand = []\n",
+                        "[env]\nA = \"os\"\nB = \"os\"\nC = \"os\"\n",
+                    ),
+                ),
+                ("arch.toml", "[env]\nA = \"arch\"\nB = \"arch\"\n"),
+                ("host.toml", "[env]\nA = \"host\"\n"),
+            ],
+        );
+        let overlays = ["arch.toml", "missing.toml", "host.toml"].map(|name| dir.join(name));
+
+        let p = Platform::load_from(&dir.join("os.toml"), &overlays, &fake_home()).unwrap();
+        let s = |v: &str| EnvValue::String(v.to_owned());
+        assert_eq!(p.env["A"], s("host"));
+        assert_eq!(p.env["B"], s("arch"));
+        assert_eq!(p.env["C"], s("os"));
+        let names: Vec<_> = p.sources.iter().map(|p| p.file_name().unwrap()).collect();
+        assert_eq!(names, ["os.toml", "arch.toml", "host.toml"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn overlays_edit_inherited_lists() {
+        let dir = scratch(
+            "directives",
+            &[
+                ("common.toml", "[paths]\nPATH = [\"/usr/bin\", \"/bin\"]\n"),
+                (
+                    "os.toml",
+                    concat!(
+                        "extends = [\"common.toml\"]\n",
+                        "[package_manager]\nname = \"x\"\ninstall = []\nupgrade = []\n",
+                        "[system_update]\ncommand = []\n",
+                        "[paths]\nPATH = { prepend = [\"/opt/bin\"] }\n",
+                    ),
+                ),
+                (
+                    "site.toml",
+                    "[paths]\nPATH = { append = [\"/site\"], remove = [\"/bin\"] }\n",
+                ),
+            ],
+        );
+
+        let p = Platform::load_from(&dir.join("os.toml"), &[dir.join("site.toml")], &fake_home())
+            .unwrap();
+        let PathEntry::Multi(dirs) = &p.path_env["PATH"] else {
+            panic!("expected a list");
+        };
+        assert_eq!(dirs, &["/opt/bin", "/usr/bin", "/site"].map(PathBuf::from));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_paths_follow_policy() {
+        let home = scratch("missing", &[("bin/x", ""), ("etc/editor", "")]);
+        let config = |policy: &str| {
+            let toml = format!(
+                concat!(
+                    "missing_paths = \"{}\"\n",
+                    "[package_manager]\nname = \"x\"\ninstall = []\nupgrade = []\n",
+                    "[system_update]\ncommand = []\n",
+                    "[paths]\n",
+                    "EDITOR = \"etc/editor\"\n",
+                    "GONE = {{ path = \"etc/gone\", if_exists = true }}\n",
+                    "PAGER = \"etc/pager\"\n",
+                    "PATH = [\n",
+                    "    \"bin\",\n",
+                    "    \"nope\",\n",
+                    "    {{ path = \".ghcup/bin\", if_exists = true }},\n",
+                    "    {{ path = \"later\", if_exists = false }},\n",
+                    "]\n",
+                ),
+                policy
+            );
+            let path = home.join(format!("{policy}.toml"));
+            fs::write(&path, toml).unwrap();
+            Platform::load_from(&path, &[], &home).unwrap()
+        };
+        let dirs = |p: &Platform| match &p.path_env["PATH"] {
+            PathEntry::Multi(dirs) => dirs.clone(),
+            PathEntry::Single(_) => panic!("expected a list"),
+        };
+        let missing = |key: &str, path: &str, dropped| MissingPath {
+            key: key.to_owned(),
+            path: home.join(path),
+            dropped,
+        };
+
+        let keep = config("keep");
+        assert_eq!(dirs(&keep), ["bin", "nope", "later"].map(|d| home.join(d)));
+        assert!(keep.path_env.contains_key("EDITOR"));
+        assert!(keep.path_env.contains_key("PAGER"));
+        assert!(!keep.path_env.contains_key("GONE"));
+        assert_eq!(
+            keep.missing_paths,
+            [
+                missing("GONE", "etc/gone", true),
+                missing("PATH", ".ghcup/bin", true),
+            ]
+        );
+
+        let drop = config("drop");
+        assert_eq!(dirs(&drop), ["bin", "later"].map(|d| home.join(d)));
+        assert!(drop.path_env.contains_key("PAGER"));
+        assert_eq!(drop.missing_paths[1], missing("PATH", "nope", true));
+
+        let warn = config("warn");
+        assert_eq!(dirs(&warn), dirs(&keep));
+        assert_eq!(warn.missing_paths[1], missing("PATH", "nope", false));
+
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn extends_cycle_is_an_error() {
+        let dir = scratch(
+            "cycle",
+            &[
+                ("a.toml", "extends = [\"b.toml\"]\n"),
+                ("b.toml", "extends = [\"./a.toml\"]\n"),
+            ],
+        );
+
+        let err = read_layered(
+            &dir.join("a.toml"),
+            &mut toml::Table::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        let Error::Cycle(paths) = err else {
+            panic!("expected a cycle, got {err}");
+        };
+        let names: Vec<_> = paths.iter().map(|p| p.file_name().unwrap()).collect();
+        assert_eq!(names, ["a.toml", "b.toml", "a.toml"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extends_must_name_files() {
+        let dir = scratch("names", &[("a.toml", "extends = \"b.toml\"\n")]);
+        let err = read_layered(
+            &dir.join("a.toml"),
+            &mut toml::Table::new(),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::Extends(_)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn tilde_expansion_path() {
         let home = fake_home();
@@ -327,7 +849,7 @@ mod tests {
         let tmp = std::env::temp_dir().join("platform-test-site.toml");
         fs::write(&tmp, "[env]\nJUMP_HOME = \"~/home\"\n").unwrap();
 
-        let p = Platform::load_from(&platform_path, &tmp, &home).unwrap();
+        let p = Platform::load_from(&platform_path, std::slice::from_ref(&tmp), &home).unwrap();
         assert_eq!(
             p.env.get("JUMP_HOME").unwrap(),
             &EnvValue::String(format!("{}/home", home.display()))
@@ -353,6 +875,21 @@ mod tests {
         assert_eq!(unknown_keys(&table).count(), 0);
     }
 
+    #[test]
+    fn item_problems_flags_misread_items() {
+        let paths: toml::Table = toml::from_str(concat!(
+            "PATH = [\"bin\", { path = \"go/bin\", if_exist = true }, { path = 1 }]\n",
+            "MANPATH = { append = [{ path = \"man\", if_exists = \"yes\" }] }\n",
+            "EDITOR = { path = \"vi\", if_exists = true }\n",
+        ))
+        .unwrap();
+        let problems = item_problems(&paths);
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].starts_with("paths.PATH: unrecognized item key `if_exist`"));
+        assert!(problems[1].starts_with("paths.PATH: ignoring item"));
+        assert!(problems[2].starts_with("paths.MANPATH: `if_exists` is not a boolean"));
+    }
+
     #[test]
     fn env_preserves_toml_order() {
         let root = conf_root();
@@ -360,7 +897,7 @@ mod tests {
         let site_path = root.join("var/nonexistent-site.toml");
         let home = fake_home();
 
-        let p = Platform::load_from(&platform_path, &site_path, &home).unwrap();
+        let p = Platform::load_from(&platform_path, &[site_path], &home).unwrap();
 
         let keys: Vec<&str> = p.env.keys().map(String::as_str).collect();
         // The first key in our macos.toml [env] section is LESS.
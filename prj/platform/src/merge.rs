@@ -1,15 +1,74 @@
+use std::fmt;
+
 use toml::Table;
 use toml::Value;
 
+/// Keys of a table that edits an array, rather than replacing it.
+const DIRECTIVES: &[&str] = &["remove", "prepend", "append"];
+
+/// A value an overlay asked to remove from an array that did not hold it.
+#[derive(Debug, PartialEq)]
+pub struct Unremoved {
+    /// The dotted path of the array, such as `paths.PATH`.
+    pub key: String,
+    pub value: Value,
+}
+
+impl fmt::Display for Unremoved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}`: cannot remove {}, which is not present",
+            self.key, self.value
+        )
+    }
+}
+
 /// Deep-merge `overlay` into `base`.
 ///
 /// - Nested tables merge recursively (key by key).
 /// - Scalars and arrays in `overlay` replace the corresponding `base` value.
-pub fn deep_merge(base: &mut Table, overlay: Table) {
+/// - A table of directives, such as `{ prepend = [...] }`, `{ append = [...]
+///   }`, or `{ remove = [...] }`, edits the corresponding `base` array
+///   instead; see [`apply_directives`]. Only an array, or any key of
+///   `[paths]`, can be edited this way; elsewhere, such a table is a table.
+///
+/// Returns each value that a `remove` directive did not find.
+pub fn deep_merge(base: &mut Table, overlay: Table) -> Vec<Unremoved> {
+    let mut unremoved = Vec::new();
+    merge_at("", base, overlay, &mut unremoved);
+    unremoved
+}
+
+fn merge_at(prefix: &str, base: &mut Table, overlay: Table, unremoved: &mut Vec<Unremoved>) {
     for (key, overlay_val) in overlay {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
         match (base.get_mut(&key), overlay_val) {
             (Some(Value::Table(base_tbl)), Value::Table(overlay_tbl)) => {
-                deep_merge(base_tbl, overlay_tbl);
+                merge_at(&path, base_tbl, overlay_tbl, unremoved);
+            }
+            (base_val, Value::Table(directives))
+                if is_directives(&directives)
+                    && (prefix == "paths" || matches!(base_val, Some(Value::Array(_)))) =>
+            {
+                let items = match base_val.map(|v| std::mem::replace(v, Value::Array(Vec::new()))) {
+                    None => Vec::new(),
+                    Some(Value::Array(items)) => items,
+                    Some(scalar) => vec![scalar],
+                };
+                let items = apply_directives(&path, items, directives, unremoved);
+                base.insert(key, Value::Array(items));
+            }
+            // Merge a new table into an empty one, so that any directives
+            // within it still make arrays.
+            (None, Value::Table(overlay_tbl)) => {
+                let mut table = Table::new();
+                merge_at(&path, &mut table, overlay_tbl, unremoved);
+                base.insert(key, Value::Table(table));
             }
             (_, overlay_val) => {
                 base.insert(key, overlay_val);
@@ -18,6 +77,59 @@ pub fn deep_merge(base: &mut Table, overlay: Table) {
     }
 }
 
+/// Whether `table` edits an array, rather than replacing a value.
+pub(crate) fn is_directives(table: &Table) -> bool {
+    !table.is_empty() && table.keys().all(|k| DIRECTIVES.contains(&k.as_str()))
+}
+
+/// Edit `items`, the array at `path`: first `remove` values, then `prepend`
+/// and `append` others, and finally drop any repeated values, keeping the
+/// first of each. A directive's value may be a single item or an array.
+///
+/// Items compare by their path, whether written as a string or as a table
+/// such as `{ path = "go/bin", if_exists = true }`, and as written: before
+/// any `${KEY}` in them is interpolated.
+fn apply_directives(
+    path: &str,
+    mut items: Vec<Value>,
+    mut directives: Table,
+    unremoved: &mut Vec<Unremoved>,
+) -> Vec<Value> {
+    let mut take = |name: &str| match directives.remove(name) {
+        None => Vec::new(),
+        Some(Value::Array(values)) => values,
+        Some(value) => vec![value],
+    };
+    let (remove, prepend, append) = (take("remove"), take("prepend"), take("append"));
+
+    for value in remove {
+        let len = items.len();
+        items.retain(|item| path_of(item) != path_of(&value));
+        if items.len() == len {
+            unremoved.push(Unremoved {
+                key: path.to_owned(),
+                value,
+            });
+        }
+    }
+
+    let mut edited: Vec<Value> = Vec::new();
+    for item in prepend.into_iter().chain(items).chain(append) {
+        if !edited.iter().any(|kept| path_of(kept) == path_of(&item)) {
+            edited.push(item);
+        }
+    }
+    edited
+}
+
+/// The `path` of an item written as a table, or else the item itself.
+fn path_of(item: &Value) -> &Value {
+    match item {
+        Value::Table(table) => table.get("path").unwrap_or(item),
+        _ => item,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,6 +191,125 @@ mod tests {
         assert_eq!(arr[0].as_str().unwrap(), "/custom/bin");
     }
 
+    fn strings(value: &Value) -> Vec<&str> {
+        value
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn directives_edit_lists() {
+        let mut base: Table = toml::from_str(
+            r#"
+            [paths]
+            PATH = ["/a", "/b", "/c"]
+            "#,
+        )
+        .unwrap();
+        let overlay: Table = toml::from_str(
+            r#"
+            [paths]
+            PATH = { prepend = ["/c", "/new"], append = ["/a", "/z"], remove = "/b" }
+            "#,
+        )
+        .unwrap();
+        let unremoved = deep_merge(&mut base, overlay);
+
+        assert!(unremoved.is_empty());
+        assert_eq!(strings(&base["paths"]["PATH"]), ["/c", "/new", "/a", "/z"]);
+    }
+
+    #[test]
+    fn directives_create_lists() {
+        let mut base: Table = toml::from_str("[paths]\nSINGLE = \"/one\"\n").unwrap();
+        let overlay: Table = toml::from_str(
+            r#"
+            [paths]
+            SINGLE = { append = ["/two"] }
+            NEW = { prepend = ["/x", "/x"] }
+            "#,
+        )
+        .unwrap();
+        deep_merge(&mut base, overlay);
+
+        assert_eq!(strings(&base["paths"]["SINGLE"]), ["/one", "/two"]);
+        assert_eq!(strings(&base["paths"]["NEW"]), ["/x"]);
+    }
+
+    #[test]
+    fn items_compare_by_path() {
+        let mut base: Table = toml::from_str(
+            r#"
+            [paths]
+            PATH = ["/a", { path = "go/bin", if_exists = true }, "${CONF}/bin"]
+            "#,
+        )
+        .unwrap();
+        let overlay: Table = toml::from_str(
+            r#"
+            [paths]
+            PATH = { prepend = [{ path = "/a", if_exists = true }], remove = ["go/bin", { path = "${CONF}/bin" }] }
+            "#,
+        )
+        .unwrap();
+        let unremoved = deep_merge(&mut base, overlay);
+
+        assert!(unremoved.is_empty());
+        let path = base["paths"]["PATH"].as_array().unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0]["path"].as_str(), Some("/a"));
+    }
+
+    #[test]
+    fn directives_without_base_paths() {
+        let mut base: Table = toml::from_str("[env]\nA = \"1\"\n").unwrap();
+        let overlay: Table = toml::from_str("[paths]\nPATH = { append = [\"/x\"] }\n").unwrap();
+        deep_merge(&mut base, overlay);
+        assert_eq!(strings(&base["paths"]["PATH"]), ["/x"]);
+    }
+
+    #[test]
+    fn removing_what_is_absent_is_reported() {
+        let mut base: Table = toml::from_str("[paths]\nPATH = [\"/a\"]\n").unwrap();
+        let overlay: Table =
+            toml::from_str("[paths]\nPATH = { remove = [\"/a\", \"/gone\"] }\n").unwrap();
+        let unremoved = deep_merge(&mut base, overlay);
+
+        assert!(strings(&base["paths"]["PATH"]).is_empty());
+        assert_eq!(
+            unremoved,
+            [Unremoved {
+                key: "paths.PATH".to_owned(),
+                value: Value::from("/gone"),
+            }]
+        );
+        assert_eq!(
+            unremoved[0].to_string(),
+            "`paths.PATH`: cannot remove \"/gone\", which is not present"
+        );
+    }
+
+    #[test]
+    fn other_tables_still_merge() {
+        let mut base: Table = toml::from_str("[env]\nA = \"1\"\n").unwrap();
+        let overlay: Table = toml::from_str("[env]\nappend = \"2\"\n").unwrap();
+        deep_merge(&mut base, overlay);
+
+        let env = base["env"].as_table().unwrap();
+        assert_eq!(env["A"].as_str().unwrap(), "1");
+        assert_eq!(env["append"].as_str().unwrap(), "2");
+
+        // Nor is a new table an array, just because its keys look like
+        // directives.
+        let mut base = Table::new();
+        let overlay: Table = toml::from_str("[env]\nappend = \"2\"\n").unwrap();
+        deep_merge(&mut base, overlay);
+        assert_eq!(base["env"]["append"].as_str().unwrap(), "2");
+    }
+
     #[test]
     fn overlay_adds_new_keys() {
         let mut base: Table = toml::from_str("a = 1").unwrap();